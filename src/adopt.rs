@@ -0,0 +1,208 @@
+/// Copies (or moves with --move) an existing CMake installation into the bins
+/// directory and registers it under its detected version.
+pub fn adopt(args: &AdoptArgs, cvm_home: &Path) -> Result<(), Error> {
+    let path = &args.path;
+    let version = detect_version(path)?;
+    let origin = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let _lock = lock::acquire(cvm_home)?;
+    let installed = installed(cvm_home)?;
+    if is_installed(&version, &installed) {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("CMake v{} is already installed.", version),
+        ));
+    }
+
+    let to = cvm_home
+        .join(crate::CVM_BINS)
+        .join(format!("cmake-{}", version));
+
+    // An untracked directory is not cvm's to replace, and a failed copy below
+    // only cleans up what this call created.
+    if to.symlink_metadata().is_ok() {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!(
+                "'{}' already exists but is not a tracked version. Move it away first.",
+                to.display()
+            ),
+        ));
+    }
+
+    if args.move_install {
+        status!("Moving CMake v{} into cvm...", version);
+        if std::fs::rename(path, &to).is_err() {
+            // Renaming fails across file systems, fall back to copy + delete.
+            copy_dir(path, &to)?;
+            std::fs::remove_dir_all(path).map_err(map_error!(
+                Io,
+                "Failed to remove the original installation."
+            ))?;
+        }
+    } else {
+        status!("Copying CMake v{} into cvm...", version);
+        if let Err(error) = copy_dir(path, &to) {
+            let _ = std::fs::remove_dir_all(&to);
+            return Err(error);
+        }
+    }
+
+    set_installed(cvm_home, &version)?;
+    set_origin(cvm_home, &version, &format!("adopt:{}", origin.display()))?;
+
+    report::outcome(
+        &version,
+        Status::Adopted,
+        &format!(
+            "Adopted CMake v{}. Use 'cvm switch {}' to select it.",
+            version, version
+        ),
+    );
+    Ok(())
+}
+
+/// Registers a CMake installation in place, example a local build tree. The
+/// installation is never moved or deleted by cvm.
+pub fn link(args: &LinkArgs, cvm_home: &Path) -> Result<(), Error> {
+    let name = &args.name;
+    if !utils::is_valid_name(name) {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("'{}' cannot be used as a name for a linked version.", name),
+        ));
+    }
+
+    let path = std::fs::canonicalize(&args.path).map_err(|error| {
+        let message = format!("Failed to find '{}'.", args.path.to_str().unwrap_or(""));
+        Error::new(ErrorKind::NotFound, message).caused_by(error)
+    })?;
+
+    let version = detect_version(&path)?;
+
+    let _lock = lock::acquire(cvm_home)?;
+    let installed = installed(cvm_home)?;
+    if is_installed(name, &installed) {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("A version named '{}' is already installed.", name),
+        ));
+    }
+
+    let to = cvm_home
+        .join(crate::CVM_BINS)
+        .join(format!("cmake-{}", name));
+
+    utils::symlink_dir(&path, &to)?;
+    set_linked(cvm_home, name, &path)?;
+    set_installed(cvm_home, name)?;
+    set_origin(cvm_home, name, &format!("link:{}", path.display()))?;
+
+    report::outcome(
+        name,
+        Status::Linked,
+        &format!(
+            "Linked '{}' (CMake v{}) to '{}'. Use 'cvm switch {}' to select it.",
+            name,
+            version,
+            path.to_str().unwrap_or(""),
+            name
+        ),
+    );
+    Ok(())
+}
+
+/// Runs `cmake --version` from the given installation prefix.
+fn detect_version(prefix: &Path) -> Result<Arc<str>, Error> {
+    let cmake = utils::cmake_binary(prefix).ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "Could not find a cmake executable in '{}'.",
+                prefix.to_str().unwrap_or("")
+            ),
+        )
+    })?;
+
+    let output = Command::new(&cmake)
+        .arg("--version")
+        .output()
+        .map_err(map_error!(External, "Failed to run 'cmake --version'."))?;
+
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::External,
+            "Running 'cmake --version' did not succeed.",
+        ));
+    }
+
+    // First line looks like: cmake version 3.27.9
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("cmake version "))
+        .map(|version| Arc::from(version.trim()))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::External,
+                "Could not read the version from 'cmake --version'.",
+            )
+        })
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), Error> {
+    std::fs::create_dir(to).map_err(|error| {
+        let message = format!("Failed to create '{}'.", to.to_str().unwrap_or(""));
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })?;
+
+    let entries = std::fs::read_dir(from).map_err(map_error!(Io, "Failed to read directory."))?;
+    for entry in entries {
+        let entry = entry.map_err(map_error!(Io, "Failed to read directory entry."))?;
+        let file_type = entry
+            .file_type()
+            .map_err(map_error!(Io, "Failed to read file type."))?;
+        let target = to.join(entry.file_name());
+
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            let link = std::fs::read_link(entry.path())
+                .map_err(map_error!(Io, "Failed to read symlink."))?;
+            copy_symlink(&link, &target, &entry.path())?;
+        } else {
+            // Permissions, and therefore executable bits, are copied as well.
+            std::fs::copy(entry.path(), &target).map_err(map_error!(Io, "Failed to copy file."))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(link: &Path, target: &Path, _original: &Path) -> Result<(), Error> {
+    std::os::unix::fs::symlink(link, target).map_err(map_error!(Io, "Failed to create symlink."))
+}
+
+#[cfg(windows)]
+fn copy_symlink(link: &Path, target: &Path, original: &Path) -> Result<(), Error> {
+    let result = if original.is_dir() {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    };
+
+    result.map_err(map_error!(Io, "Failed to create symlink."))
+}
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use crate::cli::{AdoptArgs, LinkArgs};
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::macros::{map_error, status};
+use crate::releases::{installed, is_installed, set_installed, set_linked, set_origin};
+use crate::report::{self, Status};
+use crate::utils;
@@ -0,0 +1,596 @@
+/// The cvm command line. Help output is generated from these definitions.
+#[derive(Parser)]
+#[command(
+    name = "cvm",
+    version,
+    about = "CMake version manager 'cvm'.",
+    disable_version_flag = true,
+    arg_required_else_help = true,
+    after_long_help = AFTER_HELP_STR
+)]
+pub struct Cli {
+    /// Shows the version of cvm.
+    #[arg(short = 'v', long = "version", action = ArgAction::Version)]
+    pub version: Option<bool>,
+
+    /// Directory cvm keeps its state in. Defaults to $HOME/.cvm.
+    #[arg(long, global = true, value_name = "PATH", help_heading = GLOBAL_HEADING_STR)]
+    pub home: Option<PathBuf>,
+
+    /// Never use the network. Installs only use cached archives and local
+    /// sources.
+    #[arg(long, global = true, help_heading = GLOBAL_HEADING_STR)]
+    pub offline: bool,
+
+    /// Only prints errors, warnings and the requested output.
+    #[arg(short, long, global = true, help_heading = GLOBAL_HEADING_STR)]
+    pub quiet: bool,
+
+    /// Prints the causes of errors.
+    #[arg(long, global = true, help_heading = GLOBAL_HEADING_STR)]
+    pub verbose: bool,
+
+    /// Prints machine readable JSON, see the README for the schema. Implies
+    /// --quiet.
+    #[arg(long, global = true, help_heading = GLOBAL_HEADING_STR)]
+    pub json: bool,
+
+    /// Never prompts. Commands that need a choice use the project's
+    /// .cmake-version file or fail. Also set by CI=true or a missing
+    /// terminal.
+    #[arg(long, global = true, help_heading = GLOBAL_HEADING_STR)]
+    pub no_input: bool,
+
+    /// Answers yes to confirmations, such as the one of 'remove --all'.
+    #[arg(short, long, global = true, help_heading = GLOBAL_HEADING_STR)]
+    pub yes: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Shows the currently selected version.
+    Current,
+
+    /// Lists versions of CMake that can be installed, newest first. Shows the
+    /// last 10 releases by default.
+    List(ListArgs),
+
+    /// Installs CMake versions. Without a version an interactive picker is
+    /// used, or .cmake-version when prompts are off. Installing an installed
+    /// version switches to it.
+    Install(InstallArgs),
+
+    /// Switches to an installed version. Without a version an interactive
+    /// picker is used, or .cmake-version when prompts are off.
+    Switch(SwitchArgs),
+
+    /// Removes installed versions, patterns such as '3.20.*' work. Without a
+    /// version an interactive picker is used.
+    Remove(RemoveArgs),
+
+    /// Shows a release's details, the archive this host would use and, when
+    /// installed, where and how it was installed.
+    Info(InfoArgs),
+
+    /// Prints the absolute path of a tool of the selected version, example:
+    /// 'cvm which ctest'.
+    Which(WhichArgs),
+
+    /// Prints the absolute path of the directory a version is installed in.
+    ///
+    /// Every version keeps its own directory, so the path stays the same when
+    /// switching. bins/current is a link to the selected version.
+    Path(PathArgs),
+
+    /// Lists installed versions that have newer patch releases in the release
+    /// cache.
+    Outdated,
+
+    /// Installs the newest patch release of a series, the selected version's
+    /// series by default.
+    Upgrade(UpgradeArgs),
+
+    /// Shows the disk space of each installed version, the archive cache and
+    /// the total.
+    Du(DuArgs),
+
+    /// Copies an existing CMake installation into cvm. The version is detected
+    /// with 'cmake --version'.
+    Adopt(AdoptArgs),
+
+    /// Registers a CMake installation or build in place. Removing it only
+    /// unregisters it.
+    Link(LinkArgs),
+
+    /// Builds CMake from a source directory or source archive with bootstrap
+    /// and make install. Logs are kept in $HOME/.cvm/logs.
+    Build(BuildArgs),
+
+    /// Manages downloaded archives. Installs use cached archives first and
+    /// work offline once cached.
+    #[command(subcommand)]
+    Cache(CacheCommand),
+
+    /// Removes installed versions that no retention policy keeps. The
+    /// selected, protected and pinned versions are always kept.
+    Prune(PruneArgs),
+
+    /// Protects an installed version from 'cvm prune'. Lists the protected
+    /// versions without a version.
+    Protect(ProtectArgs),
+
+    /// Prints a shell completion script. Versions are completed from the
+    /// installed versions and the release cache.
+    Completions(CompletionsArgs),
+
+    /// Prints completion candidates for the shell scripts.
+    #[command(name = "__complete", hide = true)]
+    Complete(CompleteArgs),
+
+    /// Installs <version> if it is not installed and switches to it.
+    #[command(external_subcommand)]
+    Version(Vec<String>),
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Lists all releases.
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Lists installed, adopted and linked versions.
+    #[arg(short, long, conflicts_with_all = ["all", "nightlies", "available"])]
+    pub installed: bool,
+
+    /// Lists releases that are not installed.
+    #[arg(long)]
+    pub available: bool,
+
+    /// Lists nightly builds from cmake.org/files/dev.
+    #[arg(short, long)]
+    pub nightlies: bool,
+
+    /// Only lists one release series, example: 3.28, or 3.2x for 3.20 to 3.29.
+    #[arg(long, value_name = "SERIES")]
+    pub series: Option<String>,
+
+    /// Only lists versions from this one on, example: 3.25.0.
+    #[arg(long, value_name = "VERSION")]
+    pub since: Option<String>,
+
+    /// Lists at most this many versions, or series with --grouped. Defaults to
+    /// 10 when no other option is given.
+    #[arg(long, value_name = "COUNT")]
+    pub limit: Option<usize>,
+
+    /// Shows the newest release of each minor series and which versions of it
+    /// are installed.
+    #[arg(short, long)]
+    pub grouped: bool,
+
+    /// Lists releases straight from the given sources.
+    #[arg(long, value_name = "SOURCES")]
+    pub source: Option<String>,
+}
+
+#[derive(Args)]
+pub struct InstallArgs {
+    /// Versions to install, example: 3.20.3. Several versions or inclusive
+    /// ranges such as 3.25.0..3.25.3 or 3.25..3.28 are downloaded in parallel
+    /// and installed without switching. 'nightly' installs the newest nightly
+    /// build, 'nightly-YYYY-MM-DD' the one built on that day.
+    #[arg(value_name = "VERSIONS", add = ArgValueCandidates::new(completions::cached_versions))]
+    pub versions: Vec<String>,
+
+    /// Resolves the release for another platform, example: windows-x86_64.
+    #[arg(long, value_name = "OS-ARCH")]
+    pub platform: Option<String>,
+
+    /// Only downloads the archive into the current directory. Does not install
+    /// or switch.
+    #[arg(long)]
+    pub download_only: bool,
+
+    /// Installs a local .tar.gz, .tar.Z, .sh or .zip CMake archive. The
+    /// version is read from the archive name.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["platform", "package", "download_only"])]
+    pub from_file: Option<PathBuf>,
+
+    /// Version to register a --from-file archive as.
+    #[arg(long, value_name = "VERSION", requires = "from_file")]
+    pub version: Option<String>,
+
+    /// Verifies the archive checksum before extracting.
+    #[arg(long, value_name = "HEX")]
+    pub sha256: Option<String>,
+
+    /// Packaging to use for releases before 3.11 which come from
+    /// cmake.org/files: tar.gz, sh, tar.Z (2.x) or zip.
+    #[arg(long, value_name = "KIND")]
+    pub package: Option<String>,
+
+    /// Release sources to download from, in order.
+    #[arg(long, value_name = "SOURCES")]
+    pub source: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SwitchArgs {
+    /// Version to switch to, example: 3.20.2
+    #[arg(add = ArgValueCandidates::new(completions::installed_versions))]
+    pub version: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RemoveArgs {
+    /// Versions to remove, example: 3.19.0 or '3.20.*'
+    #[arg(add = ArgValueCandidates::new(completions::installed_versions))]
+    pub versions: Vec<String>,
+
+    /// Also removes the selected version, then selects the fallback.
+    #[arg(short, long, conflicts_with = "all")]
+    pub force: bool,
+
+    /// What to select after --force removed the selected version: newest,
+    /// none or a version. Defaults to 'remove_fallback' in the config, or
+    /// newest.
+    #[arg(long, value_name = "VERSION", requires = "force")]
+    pub fallback: Option<String>,
+
+    /// Removes all versions and caches (removes the whole cvm home). Shows
+    /// what goes and asks first unless --yes is given.
+    #[arg(long, conflicts_with = "versions")]
+    pub all: bool,
+
+    /// With --all, keeps the archive cache and the cvm config.
+    #[arg(long, requires = "all", conflicts_with = "bins_only")]
+    pub keep_cache: bool,
+
+    /// With --all, only removes the installed versions. Settings, logs and
+    /// the archive cache are kept.
+    #[arg(long, requires = "all")]
+    pub bins_only: bool,
+}
+
+#[derive(Args)]
+pub struct UpgradeArgs {
+    /// Series to upgrade, example: 3.28
+    #[arg(conflicts_with = "all")]
+    pub series: Option<String>,
+
+    /// Upgrades every installed series.
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Selects the newest patch release when the selected version was
+    /// superseded.
+    #[arg(short, long)]
+    pub switch: bool,
+
+    /// Points the project's .cmake-version at the newest patch release.
+    #[arg(short, long)]
+    pub pin: bool,
+
+    /// Removes the superseded versions. The selected version is kept unless
+    /// --switch moves the selection.
+    #[arg(short, long)]
+    pub remove: bool,
+}
+
+#[derive(Args)]
+pub struct InfoArgs {
+    /// Version to show, example: 3.28.1
+    #[arg(add = ArgValueCandidates::new(completions::cached_versions))]
+    pub version: String,
+}
+
+#[derive(Args)]
+pub struct WhichArgs {
+    /// Tool to locate: cmake, ctest, cpack, ccmake or cmake-gui.
+    #[arg(default_value = "cmake")]
+    pub tool: String,
+
+    /// Installed version to look in instead of the selected one.
+    #[arg(long, add = ArgValueCandidates::new(completions::installed_versions))]
+    pub version: Option<String>,
+}
+
+#[derive(Args)]
+pub struct PathArgs {
+    /// Installed version, the selected one by default.
+    #[arg(add = ArgValueCandidates::new(completions::installed_versions))]
+    pub version: Option<String>,
+}
+
+#[derive(Args)]
+pub struct AdoptArgs {
+    /// CMake installation to adopt.
+    pub path: PathBuf,
+
+    /// Moves the installation instead of copying it.
+    #[arg(long = "move")]
+    pub move_install: bool,
+}
+
+#[derive(Args)]
+pub struct LinkArgs {
+    /// Name to register the installation as.
+    pub name: String,
+
+    /// CMake installation or build to link.
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct BuildArgs {
+    /// CMake source directory or source archive.
+    #[arg(long, value_name = "PATH")]
+    pub source: PathBuf,
+
+    /// Name to register the build as. Defaults to the version of the sources.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Number of parallel jobs to build with.
+    #[arg(short, long, value_name = "COUNT")]
+    pub jobs: Option<usize>,
+
+    /// Extra options passed to bootstrap after '--'.
+    #[arg(last = true, value_name = "BOOTSTRAP OPTIONS")]
+    pub bootstrap_args: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Lists cached archives.
+    List,
+
+    /// Shows how much space the cache uses.
+    Size,
+
+    /// Removes all cached archives or those of the given versions.
+    Clean {
+        /// Versions to remove archives of.
+        versions: Vec<String>,
+    },
+
+    /// Downloads archives without installing them.
+    Prefetch(PrefetchArgs),
+}
+
+#[derive(Args)]
+pub struct PrefetchArgs {
+    /// Versions to download.
+    #[arg(required = true, value_name = "VERSIONS", add = ArgValueCandidates::new(completions::cached_versions))]
+    pub versions: Vec<String>,
+
+    /// Resolves the releases for another platform, example: windows-x86_64.
+    #[arg(long, value_name = "OS-ARCH")]
+    pub platform: Option<String>,
+
+    /// Packaging to use for releases before 3.11.
+    #[arg(long, value_name = "KIND")]
+    pub package: Option<String>,
+
+    /// Release sources to download from, in order.
+    #[arg(long, value_name = "SOURCES")]
+    pub source: Option<String>,
+}
+
+#[derive(Args)]
+pub struct PruneArgs {
+    /// Only prunes nightly builds, the newest --keep are kept.
+    #[arg(long, conflicts_with_all = ["keep_recent", "keep_latest"])]
+    pub nightlies: bool,
+
+    /// Number of nightlies to keep.
+    #[arg(long, value_name = "COUNT", default_value_t = 3)]
+    pub keep: usize,
+
+    /// Keeps the COUNT most recently selected versions.
+    #[arg(long, value_name = "COUNT")]
+    pub keep_recent: Option<usize>,
+
+    /// Keeps the newest patch release of every minor series.
+    #[arg(long)]
+    pub keep_latest: bool,
+
+    /// Keeps versions pinned by .cmake-version files in DIR and below. Can be
+    /// given several times.
+    #[arg(long, value_name = "DIR")]
+    pub scan: Vec<PathBuf>,
+
+    /// Shows what would be removed without removing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ProtectArgs {
+    /// Installed version to protect, example: 3.25.3
+    #[arg(add = ArgValueCandidates::new(completions::installed_versions))]
+    pub version: Option<String>,
+
+    /// Removes the protection instead.
+    #[arg(long, requires = "version")]
+    pub remove: bool,
+}
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    /// Shell to print the completion script for.
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(Args)]
+pub struct DuArgs {
+    /// Orders versions newest first or largest first.
+    #[arg(long, value_enum, default_value_t = DuSort::Version)]
+    pub sort: DuSort,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DuSort {
+    Version,
+    Size,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Pwsh,
+    Nu,
+}
+
+#[derive(Args)]
+pub struct CompleteArgs {
+    /// Index of the word to complete, the last word by default.
+    #[arg(long)]
+    pub index: Option<usize>,
+
+    /// The command line being completed, starting with 'cvm'.
+    #[arg(last = true)]
+    pub words: Vec<String>,
+}
+
+/// Makes the global flags available to every command.
+pub fn set_globals(cli: &Cli) {
+    OFFLINE.store(cli.offline, Ordering::Relaxed);
+    // Progress messages would break the JSON document on standard output.
+    QUIET.store(cli.quiet || cli.json, Ordering::Relaxed);
+    JSON.store(cli.json, Ordering::Relaxed);
+    VERBOSE.store(cli.verbose, Ordering::Relaxed);
+    NO_INPUT.store(cli.no_input, Ordering::Relaxed);
+    YES.store(cli.yes, Ordering::Relaxed);
+}
+
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+pub fn yes() -> bool {
+    YES.load(Ordering::Relaxed)
+}
+
+/// Whether cvm may prompt. Pickers would hang or fail in pipelines, so CI=true,
+/// --no-input and a missing terminal turn prompts off.
+pub fn interactive() -> bool {
+    let ci = std::env::var("CI")
+        .map(|value| value.eq_ignore_ascii_case("true") || value == "1")
+        .unwrap_or(false);
+
+    !NO_INPUT.load(Ordering::Relaxed)
+        && !ci
+        && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+}
+
+/// The error for a prompt that cvm may not show. `hint` tells how to avoid
+/// the prompt.
+pub fn no_input_error(hint: &str) -> Error {
+    Error::new(
+        ErrorKind::Usage,
+        format!("{} {}", NOT_INTERACTIVE_STR, hint),
+    )
+}
+
+/// Asks to go ahead with something destructive. --yes skips the question.
+pub fn confirm(question: &str) -> Result<bool, Error> {
+    if yes() {
+        return Ok(true);
+    }
+
+    if !interactive() {
+        return Err(no_input_error(CONFIRM_HINT_STR));
+    }
+
+    let result = List::<bool>::new(question)
+        .add_item("No", false)
+        .add_item("Yes", true)
+        .inquire();
+
+    match result {
+        Ok(answer) => Ok(answer),
+        Err(InquiryMessage::CloseRequested) => {
+            println!();
+            Ok(false)
+        }
+        Err(_) => Err("Inquiry failed. exiting session".into()),
+    }
+}
+
+/// Fails requests to `url` when running with --offline.
+pub fn ensure_online(url: &str) -> Result<(), Error> {
+    if offline() {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("Not requesting '{}' while offline.", url),
+        ));
+    }
+
+    Ok(())
+}
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static JSON: AtomicBool = AtomicBool::new(false);
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+static YES: AtomicBool = AtomicBool::new(false);
+
+const NOT_INTERACTIVE_STR: &str =
+    "Cannot prompt without a terminal, with CI=true or with --no-input.";
+const CONFIRM_HINT_STR: &str = "Pass --yes to confirm.";
+const GLOBAL_HEADING_STR: &str = "Global options";
+const AFTER_HELP_STR: &str = "\
+Run 'cvm <version>' to install a version if needed and switch to it.
+
+Release sources:
+    Releases are listed and downloaded from the sources set with
+    'sources = ...' in $HOME/.cvm/cvm_config, tried in order. The default is
+    'github, cmake.org'. Sources are separated by commas:
+        github              Kitware/CMake github releases.
+        cmake.org           The cmake.org/files directory listing.
+        mirror:<url>        A server with the cmake.org/files layout.
+        local:<path>        A directory with release archives.
+
+Config:
+    $HOME/.cvm/cvm_config holds 'key = value' lines:
+        sources             Release sources, see above.
+        cache_dir           Directory for cached archives, can be shared.
+        nightly_keep        Prunes nightlies after every nightly install.
+        remove_fallback     What 'remove --force' selects: newest, none or a
+                            version.
+
+Project pins:
+    A .cmake-version file holding a version, found in the current directory
+    or a parent, is used by install and switch when cvm may not prompt.";
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
+use term_inquiry::{InquiryMessage, List};
+
+use crate::completions;
+use crate::error::{Error, ErrorKind};
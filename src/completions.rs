@@ -0,0 +1,169 @@
+/// Prints the script that registers completions for `shell`. The scripts ask
+/// 'cvm __complete' for candidates on every completion.
+pub fn print_script(shell: Shell) {
+    let script = match shell {
+        Shell::Bash => BASH_SCRIPT_STR,
+        Shell::Zsh => ZSH_SCRIPT_STR,
+        Shell::Fish => FISH_SCRIPT_STR,
+        Shell::Pwsh => PWSH_SCRIPT_STR,
+        Shell::Nu => NU_SCRIPT_STR,
+    };
+
+    print!("{}", script);
+}
+
+/// Prints the candidates for the word at `args.index` of `args.words`, one per
+/// line with an optional tab separated description. Only reads local state,
+/// the network is never used.
+pub fn complete(args: &CompleteArgs, cvm_home: &Path) -> Result<(), Error> {
+    let home = home_from_words(&args.words).unwrap_or_else(|| cvm_home.to_path_buf());
+    let _ = HOME.set(home);
+
+    let words = args
+        .words
+        .iter()
+        .map(OsString::from)
+        .collect::<Vec<OsString>>();
+    let index = args.index.unwrap_or(words.len().saturating_sub(1));
+    let current_dir = std::env::current_dir().ok();
+
+    let candidates = engine::complete(&mut Cli::command(), words, index, current_dir.as_deref())
+        .map_err(map_error!(Other, "Failed to complete arguments."))?;
+
+    for candidate in candidates
+        .iter()
+        .filter(|candidate| !candidate.is_hide_set())
+    {
+        let value = candidate.get_value().to_string_lossy();
+        match candidate.get_help() {
+            Some(help) => println!("{}\t{}", value, help),
+            None => println!("{}", value),
+        }
+    }
+
+    Ok(())
+}
+
+/// Versions that are installed, adopted or linked.
+pub fn installed_versions() -> Vec<CompletionCandidate> {
+    let installed = HOME
+        .get()
+        .and_then(|home| releases::installed(home).ok())
+        .unwrap_or_default();
+
+    installed
+        .iter()
+        .map(|version| CompletionCandidate::new(version.as_ref()))
+        .collect()
+}
+
+/// Releases from the release cache, installed ones are marked.
+pub fn cached_versions() -> Vec<CompletionCandidate> {
+    let home = match HOME.get() {
+        Some(home) => home,
+        None => return Vec::new(),
+    };
+
+    let installed = releases::installed(home).unwrap_or_default();
+    let mut releases = releases::cached_releases(home).unwrap_or_default();
+    releases.sort_by(|a, b| utils::compare_versions(b, a));
+
+    releases
+        .iter()
+        .map(|release| {
+            let help = if releases::is_installed(release, &installed) {
+                Some(INSTALLED_STR.into())
+            } else {
+                None
+            };
+
+            CompletionCandidate::new(release.as_ref()).help(help)
+        })
+        .collect()
+}
+
+/// The shell may be completing a command line that sets its own cvm home.
+fn home_from_words(words: &[String]) -> Option<PathBuf> {
+    words.iter().enumerate().find_map(|(index, word)| {
+        if let Some(path) = word.strip_prefix("--home=") {
+            Some(PathBuf::from(path))
+        } else if word == "--home" {
+            words.get(index + 1).map(PathBuf::from)
+        } else {
+            None
+        }
+    })
+}
+
+static HOME: OnceLock<PathBuf> = OnceLock::new();
+
+const INSTALLED_STR: &str = "installed";
+const BASH_SCRIPT_STR: &str = r#"# cvm completions for bash.
+# Add 'source <(cvm completions bash)' to ~/.bashrc.
+_cvm() {
+    local IFS=$'\n'
+    COMPREPLY=($(cvm __complete --index "$COMP_CWORD" -- "${COMP_WORDS[@]}" 2>/dev/null | cut -f1))
+}
+complete -o default -F _cvm cvm
+"#;
+const ZSH_SCRIPT_STR: &str = r#"#compdef cvm
+# cvm completions for zsh.
+# Add 'source <(cvm completions zsh)' to ~/.zshrc after compinit.
+_cvm() {
+    local -a candidates
+    candidates=("${(@f)$(cvm __complete --index $((CURRENT - 1)) -- "${words[@]}" 2>/dev/null | cut -f1)}")
+    compadd -a candidates
+}
+compdef _cvm cvm
+"#;
+const FISH_SCRIPT_STR: &str = r#"# cvm completions for fish.
+# Run 'cvm completions fish > ~/.config/fish/completions/cvm.fish'.
+function __cvm_complete
+    set -l words (commandline -opc) (commandline -ct)
+    cvm __complete --index (math (count $words) - 1) -- $words 2>/dev/null
+end
+complete -c cvm -f -a '(__cvm_complete)'
+"#;
+const PWSH_SCRIPT_STR: &str = r#"# cvm completions for PowerShell.
+# Add 'cvm completions pwsh | Out-String | Invoke-Expression' to $PROFILE.
+Register-ArgumentCompleter -Native -CommandName cvm -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements | ForEach-Object { $_.ToString() })
+    if ($wordToComplete -eq '') { $words += '' }
+    cvm __complete --index ($words.Count - 1) -- @words 2>$null | ForEach-Object {
+        $value, $help = $_ -split "`t", 2
+        if (-not $help) { $help = $value }
+        [System.Management.Automation.CompletionResult]::new($value, $value, 'ParameterValue', $help)
+    }
+}
+"#;
+const NU_SCRIPT_STR: &str = r#"# cvm completions for nushell.
+# Save as a file and 'source' it from config.nu. This replaces the external
+# completer, merge it with yours if you already have one.
+$env.config.completions.external = {
+    enable: true
+    completer: {|spans|
+        if $spans.0 == "cvm" {
+            cvm __complete --index (($spans | length) - 1) -- ...$spans
+                | lines
+                | each {|line|
+                    let parts = ($line | split row "\t")
+                    {value: ($parts | first), description: ($parts | skip 1 | str join " ")}
+                }
+        }
+    }
+}
+"#;
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use clap::CommandFactory;
+use clap_complete::engine::{self, CompletionCandidate};
+
+use crate::cli::{Cli, CompleteArgs, Shell};
+use crate::error::Error;
+use crate::macros::map_error;
+use crate::releases;
+use crate::utils;
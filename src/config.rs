@@ -0,0 +1,27 @@
+/// Reads `key` from the cvm config file. The file holds one `key = value`
+/// per line, lines starting with '#' are ignored. A missing file or key is not
+/// an error.
+pub fn value(cvm_home: &Path, key: &str) -> Result<Option<String>, Error> {
+    let file_path = cvm_home.join(crate::CVM_CONFIG);
+    if !file_path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(file_path)
+        .map_err(map_error!(Io, "Failed to read cvm_config file."))?;
+
+    let value = contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim().to_string());
+
+    Ok(value)
+}
+
+use std::path::Path;
+
+use crate::error::Error;
+use crate::macros::map_error;
@@ -0,0 +1,135 @@
+/// What went wrong, decides the exit code of cvm. The codes are part of the
+/// documented interface and never change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// Anything without a more specific kind.
+    Other,
+    /// An invalid argument, version, platform or config value.
+    Usage,
+    /// A release source or download could not be reached, or is refused while
+    /// offline.
+    Network,
+    /// A release, archive or file does not exist.
+    NotFound,
+    /// An archive does not match its expected sha256.
+    ChecksumMismatch,
+    /// The version is not installed.
+    NotInstalled,
+    /// The files in the cvm home are missing or unreadable.
+    StateCorrupt,
+    /// Another cvm process held the lock for too long.
+    LockTimeout,
+    /// The interactive picker was closed.
+    UserCancelled,
+    /// Reading or writing files failed.
+    Io,
+    /// An archive could not be extracted.
+    Archive,
+    /// A program cvm runs, such as cmake or bootstrap, failed.
+    External,
+}
+
+impl ErrorKind {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::Network => 3,
+            ErrorKind::NotFound => 4,
+            ErrorKind::ChecksumMismatch => 5,
+            ErrorKind::NotInstalled => 6,
+            ErrorKind::StateCorrupt => 7,
+            ErrorKind::LockTimeout => 8,
+            ErrorKind::Io => 9,
+            ErrorKind::Archive => 10,
+            ErrorKind::External => 11,
+            ErrorKind::UserCancelled => 130,
+        }
+    }
+}
+
+/// An error of a cvm command. Carries the error that caused it, if any.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    message: Arc<str>,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl Into<Arc<str>>) -> Self {
+        Error {
+            kind,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn caused_by(mut self, source: impl error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The messages of the errors that led to this one, outermost first.
+    pub fn causes(&self) -> Vec<String> {
+        let mut causes = Vec::new();
+        let mut source = error::Error::source(self);
+
+        while let Some(error) = source {
+            // Our own errors would repeat their cause in the message.
+            match error.downcast_ref::<Error>() {
+                Some(error) => causes.push(error.message().to_string()),
+                None => causes.push(error.to_string()),
+            }
+
+            source = error.source();
+        }
+
+        causes
+    }
+}
+
+/// Shows the message and the error that directly caused it.
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{} ({})", self.message, source),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::new(ErrorKind::Other, message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::new(ErrorKind::Other, message)
+    }
+}
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::Serialize;
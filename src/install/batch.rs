@@ -0,0 +1,313 @@
+/// Where a version's archive came from.
+enum Fetched {
+    Cached(PathBuf),
+    /// The archive and the name of the source it came from.
+    Downloaded(Vec<u8>, Arc<str>),
+}
+
+/// Expands ranges such as `3.25.0..3.25.3` or `3.25..3.28` against the cached
+/// releases and resolves nightlies. Ranges are inclusive, release candidates
+/// are left out.
+pub fn expand_versions(
+    versions: &[Arc<str>],
+    sources: &[Source],
+    cvm_home: &Path,
+) -> Result<Vec<Arc<str>>, Error> {
+    let mut expanded: Vec<Arc<str>> = Vec::new();
+
+    for version in versions {
+        let resolved = if let Some((from, to)) = version.split_once("..") {
+            releases_between(from, to, cvm_home)?
+        } else if version.starts_with(super::NIGHTLY_STR) {
+            vec![super::nightly_tag(version, sources)?]
+        } else {
+            vec![version.clone()]
+        };
+
+        for version in resolved {
+            if !expanded.contains(&version) {
+                expanded.push(version);
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
+fn releases_between(from: &str, to: &str, cvm_home: &Path) -> Result<Vec<Arc<str>>, Error> {
+    let lower = utils::parse_version(from)?;
+    let mut upper = utils::parse_version(to)?;
+
+    // `3.28` as the upper end includes every 3.28 patch release.
+    if to.split('.').count() == 2 {
+        upper.patch = i32::MAX;
+    }
+
+    let mut releases = cached_releases(cvm_home)?
+        .into_iter()
+        .filter(|release| utils::is_version_number(release))
+        .filter(|release| {
+            utils::parse_version(release)
+                .map(|version| lower <= version && version <= upper)
+                .unwrap_or(false)
+        })
+        .collect::<Vec<Arc<str>>>();
+
+    if releases.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("There are no cached releases between {} and {}.", from, to),
+        ));
+    }
+
+    releases.sort_by(|a, b| utils::compare_versions(a, b));
+    Ok(releases)
+}
+
+/// Installs several versions without changing the selected one and prints a
+/// summary.
+pub fn install_many(
+    versions: &[Arc<str>],
+    sources: &[Source],
+    options: &InstallOptions,
+    cvm_home: &Path,
+) -> Result<(), Error> {
+    let mut outcomes = install_all(versions, sources, options, cvm_home)?;
+
+    if cli::json() {
+        report::outcomes(&outcomes);
+    } else {
+        print_summary(&outcomes);
+    }
+
+    let total = outcomes.len();
+    report::failures(&mut outcomes, total)?;
+
+    if !options.download_only && !cli::json() {
+        println!("Use 'cvm switch <version>' to select one of them.");
+    }
+
+    Ok(())
+}
+
+/// Downloads several versions in parallel, then extracts and registers them
+/// one at a time under the state lock. Returns an outcome per version, a
+/// version that failed does not stop the others.
+pub fn install_all(
+    versions: &[Arc<str>],
+    sources: &[Source],
+    options: &InstallOptions,
+    cvm_home: &Path,
+) -> Result<Vec<Outcome>, Error> {
+    if options.from_file.is_some() || options.sha256.is_some() || options.version.is_some() {
+        return Err(Error::new(ErrorKind::Usage, BATCH_CONFLICT_STR));
+    }
+
+    let platform = match &options.platform {
+        Some(platform) => platform.clone(),
+        None => Platform::host()?,
+    };
+
+    if !options.download_only && !platform.is_host() {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("{} ({})", super::FOREIGN_PLATFORM_STR, platform),
+        ));
+    }
+
+    let releases = versions
+        .iter()
+        .filter(|version| !utils::is_nightly(version))
+        .cloned()
+        .collect::<Vec<Arc<str>>>();
+
+    super::check_releases(&releases, sources, options.sources.is_none(), cvm_home)?;
+
+    let installed_versions = installed(cvm_home)?;
+    let mut outcomes: Vec<Outcome> = Vec::new();
+    let mut pending = Vec::new();
+
+    for version in versions {
+        if !options.download_only && is_installed(version, &installed_versions) {
+            outcomes.push(Outcome::new(
+                version,
+                Status::AlreadyInstalled,
+                "already installed",
+            ));
+        } else {
+            pending.push(version.clone());
+        }
+    }
+
+    status!("Downloading {} versions...", pending.len());
+    let fetched = download_all(&pending, &platform, sources, options.package, cvm_home);
+
+    {
+        let _lock = lock::acquire(cvm_home)?;
+
+        for (version, result) in pending.iter().zip(fetched) {
+            let outcome = result.and_then(|(asset, fetched)| {
+                register(version, &asset, fetched, options.download_only, cvm_home)
+            });
+
+            outcomes.push(match outcome {
+                Ok((status, message)) => Outcome::new(version, status, &message),
+                Err(error) => Outcome::failed(version, error),
+            });
+        }
+    }
+
+    outcomes.sort_by(|a, b| utils::compare_versions(&a.version, &b.version));
+    Ok(outcomes)
+}
+
+/// Fetches every version on a few worker threads. Results are returned in
+/// the order of `versions`.
+fn download_all(
+    versions: &[Arc<str>],
+    platform: &Platform,
+    sources: &[Source],
+    package: Option<ArchiveKind>,
+    cvm_home: &Path,
+) -> Vec<Result<(Asset, Fetched), Error>> {
+    let progress = MultiProgress::new();
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..DOWNLOAD_JOBS.min(versions.len()) {
+            scope.spawn(|| {
+                while let Some(version) = versions.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let bar = progress.add(super::progress_bar(version));
+                    let result = fetch_version(version, platform, sources, package, cvm_home, &bar);
+
+                    match &result {
+                        Ok((_, Fetched::Cached(_))) => bar.finish_with_message("cached"),
+                        Ok((_, Fetched::Downloaded(..))) => bar.finish_with_message("done"),
+                        Err(_) => bar.abandon_with_message("failed"),
+                    }
+
+                    if let Ok(mut results) = results.lock() {
+                        results.push((version.clone(), result));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    versions
+        .iter()
+        .map(|version| {
+            results
+                .iter()
+                .position(|(fetched, _)| fetched == version)
+                .map(|index| results.swap_remove(index).1)
+                .unwrap_or_else(|| Err(Error::from("Download did not finish.")))
+        })
+        .collect()
+}
+
+fn fetch_version(
+    version: &str,
+    platform: &Platform,
+    sources: &[Source],
+    package: Option<ArchiveKind>,
+    cvm_home: &Path,
+    bar: &ProgressBar,
+) -> Result<(Asset, Fetched), Error> {
+    let asset = helper_strings::resolve(platform, version, package)?;
+
+    if let Some(archive) = cache::lookup(cvm_home, &asset.file_name)? {
+        return Ok((asset, Fetched::Cached(archive)));
+    }
+
+    let (data, origin) = super::fetch_asset(sources, &asset.file_name, &asset.url, version, bar)?;
+    super::verify_published(sources, &asset.file_name, &asset.url, version, &data)?;
+    Ok((asset, Fetched::Downloaded(data, origin)))
+}
+
+fn register(
+    version: &str,
+    asset: &Asset,
+    fetched: Fetched,
+    download_only: bool,
+    cvm_home: &Path,
+) -> Result<(Status, String), Error> {
+    let (archive, origin) = match fetched {
+        Fetched::Cached(archive) => (archive, super::CACHE_ORIGIN_STR.into()),
+        Fetched::Downloaded(data, origin) => {
+            (cache::store(cvm_home, &asset.file_name, &data)?, origin)
+        }
+    };
+
+    if download_only {
+        std::fs::copy(archive, &asset.file_name)
+            .map_err(map_error!(Io, "Failed to write download to file."))?;
+
+        return Ok((
+            Status::Downloaded,
+            format!("saved to '{}'", asset.file_name),
+        ));
+    }
+
+    // Another cvm process may have installed it while this one downloaded.
+    if is_installed(version, &installed(cvm_home)?) {
+        return Ok((Status::AlreadyInstalled, "already installed".to_string()));
+    }
+
+    super::unpack(
+        &archive,
+        asset.kind,
+        &asset.server_name,
+        &cvm_home.join(crate::CVM_BINS),
+        &format!("cmake-{}", version),
+    )?;
+
+    set_installed(cvm_home, version)?;
+    set_origin(cvm_home, version, &origin)?;
+    Ok((Status::Installed, "installed".to_string()))
+}
+
+pub fn print_summary(outcomes: &[Outcome]) {
+    println!("Summary:");
+
+    for outcome in outcomes {
+        let builder = AnsiBuilder::new().text(&format!("    {:<24} ", outcome.version));
+
+        match outcome.status {
+            Status::Failed => builder.color().fg().red(),
+            _ => builder.color().fg().green(),
+        }
+        .text(&outcome.message)
+        .reset_attributes()
+        .println();
+    }
+}
+
+const DOWNLOAD_JOBS: usize = 4;
+const BATCH_CONFLICT_STR: &str =
+    "Installing several versions cannot be combined with '--from-file', '--version' or '--sha256'.";
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use ansi_builder::AnsiBuilder;
+use indicatif::{MultiProgress, ProgressBar};
+
+use super::helper_strings::{self, ArchiveKind, Asset};
+use super::platform::Platform;
+use super::{cache, InstallOptions};
+use crate::cli;
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::macros::{map_error, status};
+use crate::releases::{cached_releases, installed, is_installed, set_installed, set_origin};
+use crate::report::{self, Outcome, Status};
+use crate::sources::Source;
+use crate::utils;
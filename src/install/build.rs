@@ -0,0 +1,273 @@
+/// Builds CMake from a source directory or source archive with `bootstrap`
+/// and `make install`, then registers the result as an installed version.
+pub fn build_version(args: &BuildArgs, cvm_home: &Path) -> Result<(), Error> {
+    if cfg!(windows) {
+        return Err(WINDOWS_UNSUPPORTED_STR.into());
+    }
+
+    // Build steps run inside the build directory, so relative paths would be
+    // resolved against it.
+    let source = &std::fs::canonicalize(&args.source).map_err(|error| {
+        let message = format!("Failed to find '{}'.", args.source.display());
+        Error::new(ErrorKind::Usage, message).caused_by(error)
+    })?;
+    let jobs = args.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+    });
+
+    let name: Arc<str> = match &args.name {
+        Some(name) => name.as_str().into(),
+        None => source_version(source)?,
+    };
+
+    if !utils::is_valid_name(&name) {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("'{}' cannot be used as a name for a build.", name),
+        ));
+    }
+
+    if is_installed(&name, &installed(cvm_home)?) {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("A version named '{}' is already installed.", name),
+        ));
+    }
+
+    let bins_path = cvm_home.join(crate::CVM_BINS);
+    let work_dir = bins_path.join(format!(".build-{}", name));
+    if work_dir.exists() {
+        std::fs::remove_dir_all(&work_dir)
+            .map_err(map_error!(Io, "Failed to clear previous build directory."))?;
+    }
+
+    std::fs::create_dir(&work_dir).map_err(map_error!(Io, "Failed to create build directory."))?;
+    let work_dir = std::fs::canonicalize(&work_dir)
+        .map_err(map_error!(Io, "Failed to resolve build directory."))?;
+
+    // The build takes minutes, the state lock is only held to register it.
+    let mut lock = None;
+    let result = build(
+        source,
+        &name,
+        jobs,
+        &args.bootstrap_args,
+        &work_dir,
+        cvm_home,
+    )
+    .and_then(|prefix| {
+        lock = Some(lock::acquire(cvm_home)?);
+
+        if is_installed(&name, &installed(cvm_home)?) {
+            return Err(Error::new(
+                ErrorKind::Usage,
+                format!("A version named '{}' was installed during the build.", name),
+            ));
+        }
+
+        std::fs::rename(prefix, bins_path.join(format!("cmake-{}", name)))
+            .map_err(map_error!(Io, "Failed to move build into place."))
+    });
+
+    std::fs::remove_dir_all(&work_dir)
+        .map_err(map_error!(Io, "Failed to cleanup build directory."))?;
+
+    result?;
+
+    set_installed(cvm_home, &name)?;
+    set_origin(cvm_home, &name, &format!("build:{}", source.display()))?;
+    drop(lock);
+
+    report::outcome(
+        &name,
+        Status::Built,
+        &format!(
+            "Built CMake '{}'. Use 'cvm switch {}' to select it.",
+            name, name
+        ),
+    );
+    Ok(())
+}
+
+/// Runs the build inside `work_dir` and returns the install prefix.
+fn build(
+    source: &Path,
+    name: &str,
+    jobs: usize,
+    bootstrap_args: &[String],
+    work_dir: &Path,
+    cvm_home: &Path,
+) -> Result<PathBuf, Error> {
+    let source_dir = if source.is_dir() {
+        source.to_path_buf()
+    } else {
+        unpack_source(source, work_dir)?
+    };
+
+    let bootstrap = source_dir.join("bootstrap");
+    if !bootstrap.is_file() {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!(
+                "'{}' does not look like a CMake source tree (missing bootstrap).",
+                source_dir.to_str().unwrap_or("")
+            ),
+        ));
+    }
+
+    let build_dir = work_dir.join("build");
+    let prefix = work_dir.join("prefix");
+    std::fs::create_dir(&build_dir).map_err(map_error!(Io, "Failed to create build directory."))?;
+
+    let logs_dir = cvm_home.join(crate::CVM_LOGS);
+    std::fs::create_dir_all(&logs_dir)
+        .map_err(map_error!(Io, "Failed to create logs directory."))?;
+
+    let log_path = logs_dir.join(format!("build-{}.log", name));
+    let log = File::create(&log_path).map_err(map_error!(Io, "Failed to create build log."))?;
+
+    status!(
+        "Building CMake '{}' (jobs: {}). Log: {}",
+        name,
+        jobs,
+        log_path.to_str().unwrap_or("")
+    );
+
+    status!("Running bootstrap...");
+    let mut bootstrap = Command::new(bootstrap);
+    bootstrap
+        .arg(format!("--prefix={}", prefix.to_str().unwrap_or("")))
+        .arg(format!("--parallel={}", jobs))
+        .args(bootstrap_args);
+    run_logged(&mut bootstrap, &build_dir, &log, &log_path)?;
+
+    status!("Running make install...");
+    let mut make = Command::new("make");
+    make.arg(format!("-j{}", jobs)).arg("install");
+    run_logged(&mut make, &build_dir, &log, &log_path)?;
+
+    if utils::cmake_binary(&prefix).is_none() {
+        return Err(Error::new(
+            ErrorKind::External,
+            format!(
+                "Build finished but no cmake executable was installed. See '{}'.",
+                log_path.to_str().unwrap_or("")
+            ),
+        ));
+    }
+
+    Ok(prefix)
+}
+
+fn unpack_source(archive: &Path, work_dir: &Path) -> Result<PathBuf, Error> {
+    let file_name = archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+
+    let (server_name, kind) = split_archive_name(file_name)?;
+    let source_dir = work_dir.join("source");
+
+    std::fs::create_dir(&source_dir)
+        .map_err(map_error!(Io, "Failed to create source directory."))?;
+
+    status!("Extracting sources...");
+    extract::extract(archive, kind, &source_dir)?;
+    root_directory(&source_dir, server_name)
+}
+
+fn run_logged(
+    command: &mut Command,
+    build_dir: &Path,
+    log: &File,
+    log_path: &Path,
+) -> Result<(), Error> {
+    let stdout = log
+        .try_clone()
+        .map_err(map_error!(Io, "Failed to open build log."))?;
+    let stderr = log
+        .try_clone()
+        .map_err(map_error!(Io, "Failed to open build log."))?;
+
+    let status = command
+        .current_dir(build_dir)
+        .stdout(stdout)
+        .stderr(stderr)
+        .status()
+        .map_err(map_error!(External, "Failed to start build step."))?;
+
+    if !status.success() {
+        return Err(Error::new(
+            ErrorKind::External,
+            format!(
+                "Build step failed ({}). See '{}' for details.",
+                status,
+                log_path.to_str().unwrap_or("")
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Takes the version from a source archive named like `cmake-3.28.1.tar.gz`
+/// or from `Source/CMakeVersion.cmake` of a source tree.
+fn source_version(source: &Path) -> Result<Arc<str>, Error> {
+    if !source.is_dir() {
+        let file_name = source
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+
+        return split_archive_name(file_name)?
+            .0
+            .strip_prefix("cmake-")
+            .filter(|version| utils::is_version_number(version))
+            .map(Arc::from)
+            .ok_or_else(|| Error::new(ErrorKind::Usage, NAME_REQUIRED_STR));
+    }
+
+    let contents = std::fs::read_to_string(source.join("Source").join("CMakeVersion.cmake"))
+        .map_err(|_| Error::new(ErrorKind::Usage, NAME_REQUIRED_STR))?;
+
+    let component = |key: &str| {
+        contents.lines().find_map(|line| {
+            line.trim()
+                .strip_prefix(&format!("set({} ", key))
+                .and_then(|rest| rest.strip_suffix(')'))
+                .map(|value| value.trim().to_string())
+        })
+    };
+
+    match (
+        component("CMake_VERSION_MAJOR"),
+        component("CMake_VERSION_MINOR"),
+        component("CMake_VERSION_PATCH"),
+    ) {
+        (Some(major), Some(minor), Some(patch)) => {
+            Ok(format!("{}.{}.{}", major, minor, patch).into())
+        }
+        _ => Err(Error::new(ErrorKind::Usage, NAME_REQUIRED_STR)),
+    }
+}
+
+const NAME_REQUIRED_STR: &str =
+    "Could not detect the CMake version of the sources. Please pass it with '--name'.";
+const WINDOWS_UNSUPPORTED_STR: &str =
+    "Building CMake from source is only supported on Linux and macOS.";
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use super::{extract, root_directory, split_archive_name};
+use crate::cli::BuildArgs;
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::macros::{map_error, status};
+use crate::releases::{installed, is_installed, set_installed, set_origin};
+use crate::report::{self, Status};
+use crate::utils;
@@ -0,0 +1,294 @@
+/// A cached archive. The archive itself is stored under its sha256 so the
+/// same download is only kept once.
+struct Entry {
+    sha256: String,
+    file_name: String,
+}
+
+/// Handles `cvm cache list|size|clean|prefetch`.
+pub fn manage_cache(command: &CacheCommand, cvm_home: &Path) -> Result<(), Error> {
+    match command {
+        CacheCommand::List => list(cvm_home),
+        CacheCommand::Size => size(cvm_home),
+        CacheCommand::Clean { versions } => clean(versions, cvm_home),
+        CacheCommand::Prefetch(args) => super::prefetch(args, cvm_home),
+    }
+}
+
+/// Returns the cache directory. `cache_dir` in the cvm config can point it
+/// somewhere shared, otherwise it lives in the cvm home.
+pub fn cache_dir(cvm_home: &Path) -> Result<PathBuf, Error> {
+    match config::value(cvm_home, "cache_dir")? {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(cvm_home.join(crate::CVM_ARCHIVES)),
+    }
+}
+
+/// Returns the cached archive named `file_name`. Archives that no longer
+/// match their checksum are skipped.
+pub fn lookup(cvm_home: &Path, file_name: &str) -> Result<Option<PathBuf>, Error> {
+    let dir = cache_dir(cvm_home)?;
+
+    for entry in entries(&dir)?.iter().rev() {
+        if entry.file_name != file_name {
+            continue;
+        }
+
+        let path = dir.join(&entry.sha256);
+        match checksum::verify(&path, &entry.sha256) {
+            Ok(_) => return Ok(Some(path)),
+            Err(error) => log::warning(&format!("Ignoring damaged cached archive. {}", error)),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the size of the newest cached archive of `version`, if any.
+pub fn archive_size(cvm_home: &Path, version: &str) -> Option<u64> {
+    let dir = cache_dir(cvm_home).ok()?;
+
+    entries(&dir)
+        .ok()?
+        .iter()
+        .rev()
+        .find(|entry| is_archive_of(&entry.file_name, version))
+        .and_then(|entry| fs::metadata(dir.join(&entry.sha256)).ok())
+        .map(|metadata| metadata.len())
+}
+
+/// Stores downloaded `data` in the cache as `file_name` and returns its path.
+pub fn store(cvm_home: &Path, file_name: &str, data: &[u8]) -> Result<PathBuf, Error> {
+    let dir = cache_dir(cvm_home)?;
+    fs::create_dir_all(&dir).map_err(map_error!(Io, "Failed to create cache directory."))?;
+
+    let sha256 = checksum::digest(data);
+    let path = dir.join(&sha256);
+
+    if !path.exists() {
+        // Written next to the final path first so other processes sharing the
+        // cache never see a partial archive.
+        let partial = dir.join(format!(".{}.part", sha256));
+        fs::write(&partial, data).map_err(map_error!(Io, "Failed to write cached archive."))?;
+        fs::rename(&partial, &path)
+            .map_err(map_error!(Io, "Failed to move archive into the cache."))?;
+    }
+
+    let known = entries(&dir)?
+        .iter()
+        .any(|entry| entry.sha256 == sha256 && entry.file_name == file_name);
+
+    if !known {
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(INDEX_FILE))
+            .map_err(map_error!(Io, "Failed to open cache index."))?;
+
+        index
+            .write_all(format!("{}  {}\n", sha256, file_name).as_bytes())
+            .map_err(map_error!(Io, "Failed to write cache index."))?;
+    }
+
+    Ok(path)
+}
+
+fn list(cvm_home: &Path) -> Result<(), Error> {
+    let dir = cache_dir(cvm_home)?;
+    let entries = entries(&dir)?;
+
+    if cli::json() {
+        let archives = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "file_name": entry.file_name,
+                    "sha256": entry.sha256,
+                    "size": fs::metadata(dir.join(&entry.sha256)).map(|metadata| metadata.len()).unwrap_or(0),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        report::print_json(&json!({ "archives": archives }));
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("There are no cached archives.");
+        return Ok(());
+    }
+
+    println!("Cached archives:");
+    for entry in &entries {
+        let size = fs::metadata(dir.join(&entry.sha256))
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        println!(
+            "    {} ({}) {}",
+            entry.file_name,
+            utils::format_size(size),
+            &entry.sha256[..12]
+        );
+    }
+
+    Ok(())
+}
+
+fn size(cvm_home: &Path) -> Result<(), Error> {
+    let dir = cache_dir(cvm_home)?;
+    let (count, bytes) = usage(&dir)?;
+
+    if cli::json() {
+        report::print_json(&json!({ "path": dir, "archives": count, "bytes": bytes }));
+        return Ok(());
+    }
+
+    println!(
+        "{} cached archives use {} in '{}'.",
+        count,
+        utils::format_size(bytes),
+        dir.to_str().unwrap_or("")
+    );
+
+    Ok(())
+}
+
+/// Removes every cached archive, or only those of the given versions.
+fn clean(versions: &[String], cvm_home: &Path) -> Result<(), Error> {
+    let dir = cache_dir(cvm_home)?;
+    let _lock = lock::acquire(cvm_home)?;
+    let (entries, removed): (Vec<Entry>, Vec<Entry>) =
+        entries(&dir)?.into_iter().partition(|entry| {
+            !versions.is_empty()
+                && !versions
+                    .iter()
+                    .any(|version| is_archive_of(&entry.file_name, version))
+        });
+
+    if removed.is_empty() {
+        if cli::json() {
+            report::print_json(&json!({ "removed": [], "freed": 0 }));
+        } else {
+            println!("No cached archives to remove.");
+        }
+
+        return Ok(());
+    }
+
+    let (_, before) = usage(&dir)?;
+
+    for entry in &removed {
+        status!("Removing cached {}...", entry.file_name);
+
+        // The same archive can be cached under more than one name.
+        if entries.iter().all(|kept| kept.sha256 != entry.sha256) {
+            let path = dir.join(&entry.sha256);
+            if path.exists() {
+                fs::remove_file(path)
+                    .map_err(map_error!(Io, "Failed to remove cached archive."))?;
+            }
+        }
+    }
+
+    write_entries(&dir, &entries)?;
+
+    let (_, after) = usage(&dir)?;
+
+    if cli::json() {
+        let removed = removed
+            .iter()
+            .map(|entry| entry.file_name.as_str())
+            .collect::<Vec<&str>>();
+
+        report::print_json(&json!({ "removed": removed, "freed": before.saturating_sub(after) }));
+        return Ok(());
+    }
+
+    println!(
+        "Removed {} cached archives, freed {}.",
+        removed.len(),
+        utils::format_size(before.saturating_sub(after))
+    );
+
+    Ok(())
+}
+
+fn is_archive_of(file_name: &str, version: &str) -> bool {
+    file_name
+        .strip_prefix("cmake-")
+        .and_then(|rest| rest.strip_prefix(version))
+        .map(|rest| rest.starts_with('-') || rest.starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// Returns the number of archives and the bytes they use.
+pub fn usage(dir: &Path) -> Result<(usize, u64), Error> {
+    let mut hashes = entries(dir)?
+        .into_iter()
+        .map(|entry| entry.sha256)
+        .collect::<Vec<String>>();
+
+    hashes.sort();
+    hashes.dedup();
+
+    let bytes = hashes
+        .iter()
+        .filter_map(|sha256| fs::metadata(dir.join(sha256)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    Ok((hashes.len(), bytes))
+}
+
+/// Reads the cache index. Each line holds a sha256 and a file name in the
+/// format written by sha256sum.
+fn entries(dir: &Path) -> Result<Vec<Entry>, Error> {
+    let index = dir.join(INDEX_FILE);
+    if !index.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents =
+        fs::read_to_string(index).map_err(map_error!(Io, "Failed to read cache index."))?;
+
+    let entries = contents
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .filter(|(sha256, _)| sha256.len() == 64)
+        .map(|(sha256, file_name)| Entry {
+            sha256: sha256.to_string(),
+            file_name: file_name.to_string(),
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+fn write_entries(dir: &Path, entries: &[Entry]) -> Result<(), Error> {
+    let contents = entries
+        .iter()
+        .map(|entry| format!("{}  {}\n", entry.sha256, entry.file_name))
+        .collect::<String>();
+
+    fs::write(dir.join(INDEX_FILE), contents)
+        .map_err(map_error!(Io, "Failed to write cache index."))
+}
+
+const INDEX_FILE: &str = "index";
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use super::checksum;
+use crate::cli::{self, CacheCommand};
+use crate::config;
+use crate::error::Error;
+use crate::lock;
+use crate::log;
+use crate::macros::{map_error, status};
+use crate::report;
+use crate::utils;
@@ -0,0 +1,72 @@
+/// Computes the sha256 of the file at `path` and compares it to `expected`.
+pub fn verify(path: &Path, expected: &str) -> Result<(), Error> {
+    let actual = file_digest(path)?;
+    compare(path.to_str().unwrap_or(""), expected, &actual)
+}
+
+/// Compares the sha256 of downloaded `data` to `expected` before it is
+/// written anywhere.
+pub fn verify_data(name: &str, data: &[u8], expected: &str) -> Result<(), Error> {
+    compare(name, expected, &digest(data))
+}
+
+/// Returns the hex encoded sha256 of `data`.
+pub fn digest(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// Finds the checksum of `file_name` in a published `cmake-<v>-SHA-256.txt`,
+/// which has a `<sha256>  <file name>` line per archive.
+pub fn listed(text: &str, file_name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (sha256, name) = line.split_once("  ")?;
+        (name.trim() == file_name).then(|| sha256.to_string())
+    })
+}
+
+fn file_digest(path: &Path) -> Result<String, Error> {
+    let mut file = File::open(path).map_err(|error| {
+        let message = format!(
+            "Failed to open '{}' for checksum.",
+            path.to_str().unwrap_or("")
+        );
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })?;
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(map_error!(Io, "Failed to read file for checksum."))?;
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn compare(name: &str, expected: &str, actual: &str) -> Result<(), Error> {
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(Error::new(
+            ErrorKind::ChecksumMismatch,
+            format!(
+                "Checksum mismatch for '{}'.\n    expected: {}\n    actual:   {}",
+                name,
+                expected.trim(),
+                actual
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Ends the name of the checksum file published with each release.
+pub const SHA256_SUFFIX: &str = "-SHA-256.txt";
+
+use std::fs::File;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, ErrorKind};
+use crate::macros::map_error;
@@ -0,0 +1,116 @@
+/// An installed version and the space it takes under bins.
+struct Usage {
+    version: Arc<str>,
+    path: PathBuf,
+    selected: bool,
+    linked: bool,
+    /// `None` for linked versions, their files are not cvm's.
+    size: Option<u64>,
+}
+
+/// Shows the disk space each installed version, the archive cache and the
+/// build logs use.
+pub fn disk_usage(args: &DuArgs, cvm_home: &Path) -> Result<(), Error> {
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+    let linked = linked(cvm_home)?;
+
+    let mut versions = Vec::new();
+    for version in installed(cvm_home)? {
+        let path = install_prefix(cvm_home, &version)?;
+        let is_linked = is_linked(&version, &linked);
+        let size = if is_linked {
+            None
+        } else {
+            Some(utils::dir_size(&path))
+        };
+
+        versions.push(Usage {
+            selected: version == current,
+            version,
+            path,
+            linked: is_linked,
+            size,
+        });
+    }
+
+    match args.sort {
+        DuSort::Version => versions.sort_by(|a, b| utils::compare_versions(&b.version, &a.version)),
+        DuSort::Size => versions.sort_by_key(|usage| Reverse(usage.size.unwrap_or(0))),
+    }
+
+    let cache_dir = cache::cache_dir(cvm_home)?;
+    let (archives, cache_bytes) = cache::usage(&cache_dir)?;
+    let logs_bytes = utils::dir_size(&cvm_home.join(crate::CVM_LOGS));
+    let versions_bytes = versions.iter().filter_map(|usage| usage.size).sum::<u64>();
+    let total = versions_bytes + cache_bytes + logs_bytes;
+
+    if cli::json() {
+        let versions = versions
+            .iter()
+            .map(|usage| {
+                json!({
+                    "version": usage.version.as_ref(),
+                    "path": usage.path,
+                    "selected": usage.selected,
+                    "linked": usage.linked,
+                    "size": usage.size,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        report::print_json(&json!({
+            "versions": versions,
+            "cache": { "path": cache_dir, "archives": archives, "bytes": cache_bytes },
+            "logs": logs_bytes,
+            "total": total,
+        }));
+        return Ok(());
+    }
+
+    for usage in &versions {
+        let size = match usage.size {
+            Some(size) => utils::format_size(size),
+            None => "-".to_string(),
+        };
+        let note = match (usage.selected, usage.linked) {
+            (true, true) => "selected, linked",
+            (true, false) => "selected",
+            (false, true) => "linked",
+            (false, false) => "",
+        };
+
+        print_row(&usage.version, &size, note);
+    }
+
+    if versions.is_empty() {
+        println!("    No versions are installed.");
+    }
+
+    print_row(
+        "Archive cache",
+        &utils::format_size(cache_bytes),
+        &format!("{} archives in '{}'", archives, cache_dir.display()),
+    );
+    print_row("Build logs", &utils::format_size(logs_bytes), "");
+    print_row("Total", &utils::format_size(total), "");
+
+    Ok(())
+}
+
+fn print_row(name: &str, size: &str, note: &str) {
+    let row = format!("    {:<24} {:>10}  {}", name, size, note);
+    println!("{}", row.trim_end());
+}
+
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::json;
+
+use super::cache;
+use crate::cli::{self, DuArgs, DuSort};
+use crate::error::Error;
+use crate::releases::{current_version, install_prefix, installed, is_linked, linked};
+use crate::report;
+use crate::utils;
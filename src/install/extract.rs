@@ -0,0 +1,211 @@
+/// Extracts the archive at `archive` into the `destination` directory. Entries
+/// that would land outside of `destination` are rejected. Executable bits and
+/// symlinks are kept as they are stored in the archive.
+pub fn extract(archive: &Path, kind: ArchiveKind, destination: &Path) -> Result<(), Error> {
+    let file = File::open(archive).map_err(|error| {
+        let message = format!(
+            "Failed to open archive '{}'.",
+            archive.to_str().unwrap_or("")
+        );
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })?;
+
+    match kind {
+        ArchiveKind::TarGz => extract_tar(GzDecoder::new(BufReader::new(file)), destination),
+        ArchiveKind::TarZ => extract_tar_z(file, destination),
+        ArchiveKind::Shell => extract_shell(file, destination),
+        ArchiveKind::Zip => extract_zip(file, destination),
+    }
+}
+
+fn extract_tar_z(mut file: File, destination: &Path) -> Result<(), Error> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(map_error!(Archive, "Failed to read archive."))?;
+
+    let tar_data = lzw::decompress(&data)?;
+    extract_tar(tar_data.as_slice(), destination)
+}
+
+/// Unpacks a self extracting `.sh` installer as created by CPack. The script
+/// header is skipped and the embedded tarball is extracted straight into
+/// `destination`, the same as running it with `--prefix --exclude-subdir`.
+fn extract_shell(mut file: File, destination: &Path) -> Result<(), Error> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(map_error!(Archive, "Failed to read installer."))?;
+
+    let offset = shell_payload_offset(&data).ok_or_else(|| {
+        archive_error("Could not find the archive embedded in the .sh installer.")
+    })?;
+
+    extract_tar(GzDecoder::new(&data[offset..]), destination)
+}
+
+/// The header ends with `tail +<lines> "$0" | gunzip | ...`. When that can not
+/// be found, fall back to the first gzip header that starts a line.
+fn shell_payload_offset(data: &[u8]) -> Option<usize> {
+    let header_lines = data
+        .split(|byte| *byte == b'\n')
+        .take(MAX_SHELL_HEADER_LINES)
+        .map(String::from_utf8_lossy)
+        .filter(|line| line.contains("tail") && line.contains("\"$0\""))
+        .find_map(|line| {
+            line.split_whitespace()
+                .find_map(|word| word.strip_prefix('+')?.parse::<usize>().ok())
+        });
+
+    if let Some(lines) = header_lines {
+        let offset = data
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'\n')
+            .nth(lines.saturating_sub(2))
+            .map(|(index, _)| index + 1)?;
+
+        if data[offset..].starts_with(&GZIP_MAGIC) {
+            return Some(offset);
+        }
+    }
+
+    data.windows(GZIP_MAGIC.len() + 1)
+        .position(|window| window[0] == b'\n' && window[1..] == GZIP_MAGIC)
+        .map(|index| index + 1)
+}
+
+fn extract_tar<R: Read>(reader: R, destination: &Path) -> Result<(), Error> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.set_overwrite(false);
+
+    let entries = archive
+        .entries()
+        .map_err(map_error!(Archive, "Failed to read tar archive."))?;
+
+    for entry in entries {
+        let mut entry = entry.map_err(map_error!(Archive, "Failed to read tar archive entry."))?;
+
+        let path = entry
+            .path()
+            .map_err(map_error!(Archive, "Tar archive contains an invalid path."))?
+            .into_owned();
+
+        if path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+            return Err(archive_error(&format!(
+                "Tar archive entry '{}' points outside of the extraction directory.",
+                path.to_str().unwrap_or("")
+            )));
+        }
+
+        let unpacked = entry.unpack_in(destination).map_err(|error| {
+            archive_error(&format!(
+                "Failed to extract '{}'.",
+                path.to_str().unwrap_or("")
+            ))
+            .caused_by(error)
+        })?;
+
+        if !unpacked {
+            return Err(archive_error(&format!(
+                "Tar archive entry '{}' points outside of the extraction directory.",
+                path.to_str().unwrap_or("")
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_zip(file: File, destination: &Path) -> Result<(), Error> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))
+        .map_err(map_error!(Archive, "Failed to read zip archive."))?;
+
+    // Sanitizes every entry path and refuses symlinks escaping `destination`.
+    archive
+        .extract(destination)
+        .map_err(map_error!(Archive, "Failed to extract zip archive."))
+}
+
+const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 0x08];
+const MAX_SHELL_HEADER_LINES: usize = 1000;
+
+fn archive_error(message: &str) -> Error {
+    Error::new(ErrorKind::Archive, message)
+}
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path};
+
+use flate2::read::GzDecoder;
+
+use super::helper_strings::ArchiveKind;
+use super::lzw;
+use crate::error::{Error, ErrorKind};
+use crate::macros::map_error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: [&str; 6] = [
+        "#!/bin/sh",
+        "# Display usage",
+        "use_new_tail_syntax=\"-n\"",
+        "tail $use_new_tail_syntax +{lines} \"$0\" | gunzip | (cd \"${toplevel}\" && tar xf -) || cpack_echo_exit \"Problem unpacking\"",
+        "",
+        "exit 0",
+    ];
+
+    /// A CPack STGZ installer, a shell header that unpacks itself with
+    /// `tail +<lines>` followed by the gzip payload. Returns the installer
+    /// and where the payload starts.
+    fn installer(header: &[&[u8]], tail_lines: usize) -> (Vec<u8>, usize) {
+        let mut data = Vec::new();
+        for line in header {
+            let line = String::from_utf8_lossy(line).replace("{lines}", &tail_lines.to_string());
+            data.extend(line.bytes());
+            data.push(b'\n');
+        }
+
+        let offset = data.len();
+        data.extend(GZIP_MAGIC);
+        data.extend(b"\x00payload");
+        (data, offset)
+    }
+
+    fn header() -> Vec<&'static [u8]> {
+        HEADER.iter().map(|line| line.as_bytes()).collect()
+    }
+
+    #[test]
+    fn finds_the_payload_after_the_tail_header() {
+        let (data, offset) = installer(&header(), HEADER.len() + 1);
+        assert_eq!(shell_payload_offset(&data), Some(offset));
+    }
+
+    #[test]
+    fn prefers_the_tail_header_over_gzip_magic_in_the_header() {
+        let mut header = header();
+        header.insert(1, b"\x1f\x8b\x08 is not the payload");
+
+        let (data, offset) = installer(&header, header.len() + 1);
+        assert_eq!(shell_payload_offset(&data), Some(offset));
+    }
+
+    #[test]
+    fn falls_back_to_the_first_gzip_line() {
+        // The tail count does not point at the payload.
+        let (data, offset) = installer(&header(), 3);
+        assert_eq!(shell_payload_offset(&data), Some(offset));
+
+        // No tail line at all.
+        let (data, offset) = installer(&header()[..3], 0);
+        assert_eq!(shell_payload_offset(&data), Some(offset));
+    }
+
+    #[test]
+    fn finds_no_payload_in_plain_scripts() {
+        assert_eq!(shell_payload_offset(b"#!/bin/sh\necho hello\n"), None);
+    }
+}
@@ -1,172 +1,211 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    TarGz,
+    TarZ,
+    Shell,
+    Zip,
+}
+
+impl ArchiveKind {
+    pub fn extension(&self) -> &str {
+        match self {
+            ArchiveKind::TarGz => "tar.gz",
+            ArchiveKind::TarZ => "tar.Z",
+            ArchiveKind::Shell => "sh",
+            ArchiveKind::Zip => "zip",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Result<Self, Error> {
+        match kind {
+            "tar.gz" | "tgz" => Ok(ArchiveKind::TarGz),
+            "tar.Z" | "tar.z" => Ok(ArchiveKind::TarZ),
+            "sh" => Ok(ArchiveKind::Shell),
+            "zip" => Ok(ArchiveKind::Zip),
+            _ => Err(Error::new(
+                ErrorKind::Usage,
+                format!(
+                    "Unknown package '{}'. Expected one of tar.gz, tar.Z, sh or zip.",
+                    kind
+                ),
+            )),
+        }
+    }
+
+    /// Self extracting installers hold the installation without the
+    /// `cmake-<version>-<platform>` directory the other archives have.
+    pub fn has_top_directory(&self) -> bool {
+        !matches!(self, ArchiveKind::Shell)
+    }
+}
+
+/// A release asset as published by Kitware for a single platform.
+pub struct Asset {
+    pub file_name: String,
+    pub kind: ArchiveKind,
+    pub server_name: String,
+    pub url: String,
+}
+
 pub struct HelperStrings {
     pub bins_path: PathBuf,
     pub download_url: String,
-    pub save_path: PathBuf,
+    pub file_name: String,
+    pub kind: ArchiveKind,
     pub server_name: String,
 }
 
 impl HelperStrings {
-    pub fn new(cvm_home: &Path, version: &str) -> Result<Self, Rc<str>> {
-        download_strings(cvm_home, version)
-    }
-}
-
-#[cfg(target_os = "macos")]
-fn pre_19_2(cvm_home: &Path, version: &str) -> HelperStrings {
-    let name = format!("cmake-{}", version);
-    let server_name = format!("{}-Darwin-x86_64", name);
-    let download_url = format!(
-        "https://github.com/Kitware/CMake/releases/download/v{}/{}.tar.gz",
-        version, server_name
-    );
-
-    let bins_path = cvm_home.join(crate::CVM_BINS);
-    let save_path = bins_path.clone().join(format!("{}.tar.gz", name));
-
-    HelperStrings {
-        bins_path,
-        download_url,
-        save_path,
-        server_name,
+    pub fn new(
+        cvm_home: &Path,
+        version: &str,
+        platform: &Platform,
+        package: Option<ArchiveKind>,
+    ) -> Result<Self, Error> {
+        let asset = resolve(platform, version, package)?;
+        let bins_path = cvm_home.join(crate::CVM_BINS);
+
+        Ok(HelperStrings {
+            bins_path,
+            download_url: asset.url,
+            file_name: asset.file_name,
+            kind: asset.kind,
+            server_name: asset.server_name,
+        })
     }
 }
 
-#[cfg(target_os = "macos")]
-fn post_19_1(cvm_home: &Path, version: &str) -> Result<HelperStrings, Rc<str>> {
-    let name = format!("cmake-{}", version);
-
-    let system = System::new();
-    let os_version = match system.get_os_version() {
-        Some(version) => version,
-        None => panic!("Failed to get the os version"),
+/// Resolves the name, url and archive kind of the asset to download for the
+/// given version on the given platform. Releases older than 3.11 are resolved
+/// against cmake.org/files, `package` only applies to those. Nightlies are
+/// resolved against cmake.org/files/dev.
+pub fn resolve(
+    platform: &Platform,
+    version: &str,
+    package: Option<ArchiveKind>,
+) -> Result<Asset, Error> {
+    let cmake_version = match utils::parse_nightly(version) {
+        Some(nightly) => nightly,
+        None => parse_version(version)?,
     };
 
-    let mac_version = parse_version(&os_version)?;
+    if legacy::is_legacy(&cmake_version) {
+        return legacy::resolve(platform, version, &cmake_version, package);
+    }
 
-    let dw_name = if mac_version.major < 10 || mac_version.major == 10 && mac_version.minor <= 10 {
-        "macos10.10-universal"
-    } else {
-        "macos-universal"
+    let kind = default_kind(platform.os);
+    if package.is_some() && package != Some(kind) {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "CMake v{} is only published as .{} for {}.",
+                version,
+                kind.extension(),
+                platform
+            ),
+        ));
+    }
+    let suffix = match platform.os {
+        Os::Linux => linux_suffix(&cmake_version, platform.arch),
+        Os::Mac => macos_suffix(&cmake_version, platform),
+        Os::Windows => windows_suffix(&cmake_version, platform.arch),
     };
 
-    let server_name = format!("{}-{}", name, dw_name);
-    let download_url = format!(
-        "https://github.com/Kitware/CMake/releases/download/v{}/{}.tar.gz",
-        version, server_name
-    );
-
-    let bins_path = cvm_home.join(crate::CVM_BINS);
-    let save_path = bins_path.clone().join(format!("{}.tar.gz", name));
+    let suffix = suffix.ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "CMake v{} does not provide binaries for {}.",
+                version, platform
+            ),
+        )
+    })?;
+
+    let server_name = format!("cmake-{}-{}", version, suffix);
+    let file_name = format!("{}.{}", server_name, kind.extension());
+    let url = match cmake_version.date {
+        Some(_) => format!("{}/{}", NIGHTLY_URL, file_name),
+        None => format!(
+            "https://github.com/Kitware/CMake/releases/download/v{}/{}",
+            version, file_name
+        ),
+    };
 
-    Ok(HelperStrings {
-        bins_path,
-        download_url,
-        save_path,
+    Ok(Asset {
+        file_name,
+        kind,
         server_name,
+        url,
     })
 }
 
-#[cfg(target_os = "macos")]
-fn download_strings(cvm_home: &Path, version: &str) -> Result<HelperStrings, Rc<str>> {
-    let cmake_version = parse_version(version)?;
-    if cmake_version.minor < 19 || cmake_version.minor == 19 && cmake_version.patch <= 1 {
-        return Ok(pre_19_2(cvm_home, version));
+pub fn default_kind(os: Os) -> ArchiveKind {
+    match os {
+        Os::Windows => ArchiveKind::Zip,
+        Os::Linux | Os::Mac => ArchiveKind::TarGz,
     }
-
-    post_19_1(cvm_home, version)
 }
 
-#[cfg(target_os = "linux")]
-#[cfg(target_arch = "x86_64")]
-fn download_strings(cvm_home: &Path, version: &str) -> Result<HelperStrings, Rc<str>> {
-    let cmake_version = parse_version(version)?;
+fn linux_suffix(version: &utils::Version, arch: Arch) -> Option<&'static str> {
+    let lowercase = at_least(version, 3, 20, 0);
 
-    let linux = if cmake_version.minor > 19 {
-        "linux"
-    } else {
-        "Linux"
-    };
-    let name = format!("cmake-{}", version);
-    let server_name = format!("{}-{}-x86_64", name, linux);
-    let download_url = format!(
-        "https://github.com/Kitware/CMake/releases/download/v{}/{}.tar.gz",
-        version, server_name
-    );
-
-    let bins_path = cvm_home.join(crate::CVM_BINS);
-    let save_path = bins_path.clone().join(format!("{}.tar.gz", name));
-
-    Ok(HelperStrings {
-        bins_path,
-        download_url,
-        save_path,
-        server_name,
-    })
+    match arch {
+        Arch::X86_64 if lowercase => Some("linux-x86_64"),
+        Arch::X86_64 => Some("Linux-x86_64"),
+        Arch::Aarch64 if lowercase => Some("linux-aarch64"),
+        Arch::Aarch64 if at_least(version, 3, 19, 0) => Some("Linux-aarch64"),
+        _ => None,
+    }
 }
 
-#[cfg(windows)]
-fn post_19(cvm_home: &Path, version: &str) -> HelperStrings {
-    let arch = if cfg!(target_arch = "x86") {
-        "windows-i386"
-    } else {
-        "windows-x86_64"
-    };
-
-    let name = format!("cmake-{}", version);
-    let server_name = format!("{}-{}", name, arch);
-    let download_url = format!(
-        "https://github.com/Kitware/CMake/releases/download/v{}/{}.zip",
-        version, server_name
-    );
-
-    let bins_path = cvm_home.join(crate::CVM_BINS);
-    let save_path = bins_path.clone().join(format!("{}.zip", name));
+fn macos_suffix(version: &utils::Version, platform: &Platform) -> Option<&'static str> {
+    if !at_least(version, 3, 19, 2) {
+        return match platform.arch {
+            Arch::X86_64 => Some("Darwin-x86_64"),
+            _ => None,
+        };
+    }
 
-    HelperStrings {
-        bins_path,
-        download_url,
-        save_path,
-        server_name,
+    if platform.arch == Arch::X86 {
+        return None;
     }
-}
 
-#[cfg(windows)]
-fn pre_20(cvm_home: &Path, version: &str) -> HelperStrings {
-    let arch = if cfg!(target_arch = "x86") {
-        "32-x86"
-    } else {
-        "64-x64"
+    // Older macOS releases only get a build targeting 10.10. Unknown versions
+    // are treated as recent.
+    let legacy = match &platform.os_version {
+        Some(mac) => mac.major < 10 || mac.major == 10 && mac.minor <= 10,
+        None => false,
     };
 
-    let name = format!("cmake-{}", version);
-    let server_name = format!("{}-win{}", name, arch);
-    let download_url = format!(
-        "https://github.com/Kitware/CMake/releases/download/v{}/{}.zip",
-        version, server_name
-    );
-
-    let bins_path = cvm_home.join(crate::CVM_BINS);
-    let save_path = bins_path.clone().join(format!("{}.zip", name));
-
-    HelperStrings {
-        bins_path,
-        download_url,
-        save_path,
-        server_name,
+    if legacy {
+        Some("macos10.10-universal")
+    } else {
+        Some("macos-universal")
     }
 }
 
-#[cfg(windows)]
-fn download_strings(cvm_home: &Path, version: &str) -> Result<HelperStrings, Rc<str>> {
-    let cmake_version = parse_version(version)?;
-    if cmake_version.minor >= 20 {
-        return Ok(post_19(cvm_home, version));
+fn windows_suffix(version: &utils::Version, arch: Arch) -> Option<&'static str> {
+    if at_least(version, 3, 20, 0) {
+        return match arch {
+            Arch::X86_64 => Some("windows-x86_64"),
+            Arch::X86 => Some("windows-i386"),
+            Arch::Aarch64 if at_least(version, 3, 24, 0) => Some("windows-arm64"),
+            Arch::Aarch64 => None,
+        };
+    }
+
+    match arch {
+        Arch::X86_64 => Some("win64-x64"),
+        Arch::X86 => Some("win32-x86"),
+        Arch::Aarch64 => None,
     }
+}
 
-    Ok(pre_20(cvm_home, version))
+pub fn at_least(version: &utils::Version, major: i32, minor: i32, patch: i32) -> bool {
+    (version.major, version.minor, version.patch) >= (major, minor, patch)
 }
 
-fn parse_version(version: &str) -> Result<utils::Version, Rc<str>> {
+fn parse_version(version: &str) -> Result<utils::Version, Error> {
     let version_clean = match version.find('-') {
         Some(index) => &version[0..index],
         None => version,
@@ -175,10 +214,165 @@ fn parse_version(version: &str) -> Result<utils::Version, Rc<str>> {
     utils::parse_version(version_clean)
 }
 
-#[cfg(target_os = "macos")]
-use sysinfo::{System, SystemExt};
+const NIGHTLY_URL: &str = "https://cmake.org/files/dev";
 
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
 
+use super::legacy;
+use super::platform::{Arch, Os, Platform};
+use crate::error::{Error, ErrorKind};
 use crate::utils;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_name(platform: &Platform, version: &str) -> Result<String, ErrorKind> {
+        resolve(platform, version, None)
+            .map(|asset| asset.file_name)
+            .map_err(|error| error.kind())
+    }
+
+    fn mac(os_version: &str) -> Platform {
+        Platform {
+            os_version: Some(utils::parse_version(os_version).unwrap()),
+            ..Platform::parse("macos-x86_64").unwrap()
+        }
+    }
+
+    #[test]
+    fn resolves_release_names() {
+        let cases = [
+            // Linux names went lowercase with 3.20, aarch64 builds start at 3.19.
+            ("linux-x86_64", "3.19.8", "cmake-3.19.8-Linux-x86_64.tar.gz"),
+            ("linux-x86_64", "3.20.0", "cmake-3.20.0-linux-x86_64.tar.gz"),
+            (
+                "linux-aarch64",
+                "3.19.0",
+                "cmake-3.19.0-Linux-aarch64.tar.gz",
+            ),
+            (
+                "linux-aarch64",
+                "3.25.1",
+                "cmake-3.25.1-linux-aarch64.tar.gz",
+            ),
+            // macOS builds are universal from 3.19.2.
+            (
+                "macos-x86_64",
+                "3.19.1",
+                "cmake-3.19.1-Darwin-x86_64.tar.gz",
+            ),
+            (
+                "macos-x86_64",
+                "3.19.2",
+                "cmake-3.19.2-macos-universal.tar.gz",
+            ),
+            (
+                "macos-aarch64",
+                "3.28.1",
+                "cmake-3.28.1-macos-universal.tar.gz",
+            ),
+            // Windows names changed with 3.20, arm64 builds start at 3.24.
+            ("windows-x86_64", "3.19.8", "cmake-3.19.8-win64-x64.zip"),
+            ("windows-x86", "3.19.8", "cmake-3.19.8-win32-x86.zip"),
+            (
+                "windows-x86_64",
+                "3.20.0",
+                "cmake-3.20.0-windows-x86_64.zip",
+            ),
+            ("windows-x86", "3.20.0", "cmake-3.20.0-windows-i386.zip"),
+            (
+                "windows-aarch64",
+                "3.24.0",
+                "cmake-3.24.0-windows-arm64.zip",
+            ),
+            // Release candidates keep their suffix.
+            (
+                "linux-x86_64",
+                "3.29.0-rc1",
+                "cmake-3.29.0-rc1-linux-x86_64.tar.gz",
+            ),
+        ];
+
+        for (platform, version, expected) in cases {
+            let platform = Platform::parse(platform).unwrap();
+            assert_eq!(
+                file_name(&platform, version).as_deref(),
+                Ok(expected),
+                "resolving {} for {}",
+                version,
+                platform
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_platforms_without_binaries() {
+        let cases = [
+            ("linux-aarch64", "3.18.6"),
+            ("linux-x86", "3.20.0"),
+            ("macos-aarch64", "3.19.1"),
+            ("macos-x86", "3.25.1"),
+            ("windows-aarch64", "3.23.5"),
+        ];
+
+        for (platform, version) in cases {
+            let platform = Platform::parse(platform).unwrap();
+            assert_eq!(
+                file_name(&platform, version),
+                Err(ErrorKind::NotFound),
+                "resolving {} for {}",
+                version,
+                platform
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_macos_10_10_builds() {
+        let cases = [
+            (
+                "10.10",
+                "3.19.2",
+                "cmake-3.19.2-macos10.10-universal.tar.gz",
+            ),
+            ("10.9", "3.25.1", "cmake-3.25.1-macos10.10-universal.tar.gz"),
+            ("10.13", "3.19.2", "cmake-3.19.2-macos-universal.tar.gz"),
+            ("14.2", "3.28.1", "cmake-3.28.1-macos-universal.tar.gz"),
+            ("10.10", "3.19.1", "cmake-3.19.1-Darwin-x86_64.tar.gz"),
+        ];
+
+        for (os_version, version, expected) in cases {
+            assert_eq!(
+                file_name(&mac(os_version), version).as_deref(),
+                Ok(expected),
+                "resolving {} for macOS {}",
+                version,
+                os_version
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_urls() {
+        let platform = Platform::parse("linux-x86_64").unwrap();
+
+        let release = resolve(&platform, "3.29.0-rc1", None).unwrap();
+        assert_eq!(
+            release.url,
+            "https://github.com/Kitware/CMake/releases/download/v3.29.0-rc1/cmake-3.29.0-rc1-linux-x86_64.tar.gz"
+        );
+        assert_eq!(release.server_name, "cmake-3.29.0-rc1-linux-x86_64");
+    }
+
+    #[test]
+    fn rejects_other_packages_for_recent_releases() {
+        let platform = Platform::parse("windows-x86_64").unwrap();
+        let error = resolve(&platform, "3.28.1", Some(ArchiveKind::TarGz))
+            .err()
+            .unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(resolve(&platform, "3.28.1", Some(ArchiveKind::Zip)).is_ok());
+    }
+}
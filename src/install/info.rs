@@ -0,0 +1,304 @@
+/// What is known about an installed version.
+struct InstallInfo {
+    path: PathBuf,
+    selected: bool,
+    linked: bool,
+    installed_at: Option<u64>,
+    source: Arc<str>,
+    size: Option<u64>,
+    binaries: Vec<&'static str>,
+}
+
+/// The archive an install would use on this host.
+struct HostAsset {
+    file_name: String,
+    url: String,
+    sha256: Option<String>,
+    cached: bool,
+}
+
+/// Shows what cvm knows about a version: its github release, the archive an
+/// install would use on this host and, when installed, the installation.
+pub fn show_info(args: &InfoArgs, cvm_home: &Path) -> Result<(), Error> {
+    let version = args.version.as_str();
+
+    let details = match releases::release_details(version) {
+        Ok(details) => Some(details),
+        Err(error) => {
+            // Going without details is expected while offline.
+            if !cli::offline() {
+                log::warning(&format!("Release details are not available. {}", error));
+            }
+
+            None
+        }
+    };
+
+    let cached = cached_release_info(cvm_home)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|release| release.version.as_ref() == version);
+
+    let install = install_info(version, cvm_home)?;
+
+    if details.is_none() && cached.is_none() && install.is_none() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "CMake v{} is not a known release and is not installed.",
+                version
+            ),
+        ));
+    }
+
+    let published = details
+        .as_ref()
+        .and_then(|details| details.published.clone())
+        .or_else(|| cached.and_then(|release| release.published));
+    let prerelease = details
+        .as_ref()
+        .map(|details| details.prerelease)
+        .unwrap_or_else(|| utils::is_prerelease(version));
+    let assets = details
+        .as_ref()
+        .map(|details| details.assets.clone())
+        .unwrap_or_default();
+
+    let host_asset = host_asset(version, &assets, cvm_home)?;
+
+    if cli::json() {
+        let install = install.as_ref().map(|install| {
+            json!({
+                "path": install.path,
+                "selected": install.selected,
+                "linked": install.linked,
+                "installed_at": install.installed_at.map(utils::format_timestamp),
+                "source": install.source.as_ref(),
+                "size": install.size,
+                "binaries": install.binaries,
+            })
+        });
+        let host_asset = host_asset.as_ref().map(|asset| {
+            json!({
+                "file_name": asset.file_name,
+                "url": asset.url,
+                "sha256": asset.sha256,
+                "cached": asset.cached,
+            })
+        });
+
+        report::print_json(&json!({
+            "version": version,
+            "published": published.as_deref(),
+            "prerelease": prerelease,
+            "notes": details.as_ref().and_then(|details| details.notes.as_deref()),
+            "assets": assets,
+            "host_asset": host_asset,
+            "install": install,
+        }));
+        return Ok(());
+    }
+
+    println!("CMake v{}", version);
+    print_field("Published", published.as_deref().unwrap_or("unknown"));
+    print_field("Pre-release", if prerelease { "yes" } else { "no" });
+
+    match &install {
+        Some(install) => {
+            let state = match (install.selected, install.linked) {
+                (true, true) => "yes, linked and selected",
+                (true, false) => "yes, selected",
+                (false, true) => "yes, linked",
+                (false, false) => "yes",
+            };
+
+            print_field("Installed", state);
+            print_field("Path", &install.path.display().to_string());
+
+            let installed_at = install
+                .installed_at
+                .map(utils::format_timestamp)
+                .unwrap_or_else(|| "unknown".to_string());
+            print_field("Installed at", &installed_at);
+            print_field("Source", &install.source);
+
+            if let Some(size) = install.size {
+                print_field("Size", &utils::format_size(size));
+            }
+
+            print_field("Binaries", &install.binaries.join(", "));
+        }
+        None => print_field("Installed", "no"),
+    }
+
+    if let Some(asset) = &host_asset {
+        let cached = if asset.cached { " (cached)" } else { "" };
+        print_field("Host archive", &format!("{}{}", asset.file_name, cached));
+        print_field("SHA-256", asset.sha256.as_deref().unwrap_or("unknown"));
+    }
+
+    if !assets.is_empty() {
+        println!("Assets:");
+        for asset in &assets {
+            let host = match &host_asset {
+                Some(host_asset) if host_asset.file_name == *asset.name => "(this host)",
+                _ => "",
+            };
+
+            println!(
+                "    {:<48} {:>10}  {}",
+                asset.name,
+                utils::format_size(asset.size),
+                host
+            );
+        }
+    }
+
+    if let Some(notes) = details
+        .as_ref()
+        .and_then(|details| details.notes.as_deref())
+    {
+        let lines = notes
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>();
+
+        if !lines.is_empty() {
+            println!("Release notes:");
+            for line in lines.iter().take(NOTES_LINES) {
+                println!("    {}", line);
+            }
+
+            if lines.len() > NOTES_LINES {
+                println!("    ...");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn install_info(version: &str, cvm_home: &Path) -> Result<Option<InstallInfo>, Error> {
+    if !is_installed(version, &installed(cvm_home)?) {
+        return Ok(None);
+    }
+
+    let selected = *current_version(cvm_home).unwrap_or_else(|_| "".into()) == *version;
+    let linked = is_linked(version, &linked(cvm_home)?);
+    let path = install_prefix(cvm_home, version)?;
+
+    let (installed_at, source) = match origin(cvm_home, version) {
+        Some(origin) => (Some(origin.installed_at), origin.source),
+        None => (modified_at(&path), UNKNOWN_SOURCE_STR.into()),
+    };
+
+    // Linked versions are someone else's files.
+    let size = if linked {
+        None
+    } else {
+        Some(utils::dir_size(&path))
+    };
+
+    let bin_dir = utils::bin_dir(&path);
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    let binaries = TOOLS
+        .iter()
+        .copied()
+        .filter(|tool| bin_dir.join(format!("{}{}", tool, suffix)).is_file())
+        .collect();
+
+    Ok(Some(InstallInfo {
+        path,
+        selected,
+        linked,
+        installed_at,
+        source,
+        size,
+        binaries,
+    }))
+}
+
+/// Resolves the archive for this host. The checksum comes from the cache, or
+/// from the SHA-256 file github publishes next to the archives.
+fn host_asset(
+    version: &str,
+    assets: &[ReleaseAsset],
+    cvm_home: &Path,
+) -> Result<Option<HostAsset>, Error> {
+    let asset = match helper_strings::resolve(&Platform::host()?, version, None) {
+        Ok(asset) => asset,
+        // Linked names and builds have no release archive.
+        Err(_) => return Ok(None),
+    };
+
+    if let Some(archive) = cache::lookup(cvm_home, &asset.file_name)? {
+        let sha256 = archive
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(str::to_string);
+
+        return Ok(Some(HostAsset {
+            file_name: asset.file_name,
+            url: asset.url,
+            sha256,
+            cached: true,
+        }));
+    }
+
+    let sha256 = assets
+        .iter()
+        .find(|asset| asset.name.ends_with(checksum::SHA256_SUFFIX))
+        .and_then(|checksums| match sources::get_text(&checksums.url) {
+            Ok(text) => Some(text),
+            Err(error) => {
+                log::warning(&format!("Could not get the checksums. {}", error));
+                None
+            }
+        })
+        .and_then(|text| checksum::listed(&text, &asset.file_name));
+
+    Ok(Some(HostAsset {
+        file_name: asset.file_name,
+        url: asset.url,
+        sha256,
+        cached: false,
+    }))
+}
+
+fn modified_at(path: &Path) -> Option<u64> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
+fn print_field(name: &str, value: &str) {
+    println!("    {:<14} {}", name, value);
+}
+
+const TOOLS: [&str; 5] = ["cmake", "ctest", "cpack", "ccmake", "cmake-gui"];
+const NOTES_LINES: usize = 12;
+const UNKNOWN_SOURCE_STR: &str = "unknown";
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use serde_json::json;
+
+use super::cache;
+use super::checksum;
+use super::helper_strings;
+use super::platform::Platform;
+use crate::cli::{self, InfoArgs};
+use crate::error::{Error, ErrorKind};
+use crate::log;
+use crate::releases::{
+    self, cached_release_info, current_version, install_prefix, installed, is_installed, is_linked,
+    linked, origin, ReleaseAsset,
+};
+use crate::report;
+use crate::sources;
+use crate::utils;
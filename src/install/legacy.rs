@@ -0,0 +1,172 @@
+/// Releases before this version are not published on github and follow the
+/// older naming used on cmake.org/files.
+pub fn is_legacy(version: &utils::Version) -> bool {
+    !at_least(version, 3, 11, 0)
+}
+
+/// Resolves a release that is only available from cmake.org/files. These use
+/// `Linux-i386`/`Darwin`/`win32` names and may ship as `.sh` installers or,
+/// for 2.x, as `.tar.Z`.
+pub fn resolve(
+    platform: &Platform,
+    version: &str,
+    cmake_version: &utils::Version,
+    package: Option<ArchiveKind>,
+) -> Result<Asset, Error> {
+    let suffix = suffix(cmake_version, platform).map_err(|hint| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "CMake v{} does not provide binaries for {}. {}",
+                version, platform, hint
+            ),
+        )
+    })?;
+
+    let kind = package.unwrap_or_else(|| default_kind(platform.os));
+    let supported = match (platform.os, kind) {
+        (Os::Windows, ArchiveKind::Zip) => true,
+        (Os::Linux, ArchiveKind::Shell) => true,
+        (Os::Linux | Os::Mac, ArchiveKind::TarGz) => true,
+        (Os::Linux | Os::Mac, ArchiveKind::TarZ) => cmake_version.major < 3,
+        _ => false,
+    };
+
+    if !supported {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "CMake v{} is not published as .{} for {}.",
+                version,
+                kind.extension(),
+                platform
+            ),
+        ));
+    }
+
+    let server_name = format!("cmake-{}-{}", version, suffix);
+    let file_name = format!("{}.{}", server_name, kind.extension());
+    let url = format!(
+        "https://cmake.org/files/v{}.{}/{}",
+        cmake_version.major, cmake_version.minor, file_name
+    );
+
+    Ok(Asset {
+        file_name,
+        kind,
+        server_name,
+        url,
+    })
+}
+
+/// Returns the platform part of the asset name or a hint on what is
+/// available instead.
+fn suffix(version: &utils::Version, platform: &Platform) -> Result<&'static str, &'static str> {
+    match (platform.os, platform.arch) {
+        (Os::Linux, Arch::X86_64) if at_least(version, 3, 1, 0) => Ok("Linux-x86_64"),
+        (Os::Linux, Arch::X86_64) => Err(I386_ONLY_STR),
+        (Os::Linux, Arch::X86) if !at_least(version, 3, 7, 0) => Ok("Linux-i386"),
+        (Os::Linux, Arch::X86) => Err(X86_64_ONLY_STR),
+        (Os::Mac, Arch::X86_64) if at_least(version, 3, 1, 0) => Ok("Darwin-x86_64"),
+        (Os::Mac, Arch::X86_64) => Ok("Darwin64-universal"),
+        (Os::Windows, Arch::X86_64) if at_least(version, 3, 6, 0) => Ok("win64-x64"),
+        (Os::Windows, Arch::X86_64) | (Os::Windows, Arch::X86) => Ok("win32-x86"),
+        (_, Arch::Aarch64) => Err(NO_ARM_STR),
+        (Os::Mac, Arch::X86) => Err(NO_MAC_X86_STR),
+    }
+}
+
+const I386_ONLY_STR: &str =
+    "Only Linux-i386 builds exist for this release. Use '--platform linux-x86' to install them.";
+const X86_64_ONLY_STR: &str = "Linux-i386 builds stopped with 3.6. Use linux-x86_64 instead.";
+const NO_ARM_STR: &str = "ARM builds were first published with 3.19.";
+const NO_MAC_X86_STR: &str = "Only x86_64 builds were published for macOS.";
+
+use super::helper_strings::{at_least, default_kind, ArchiveKind, Asset};
+use super::platform::{Arch, Os, Platform};
+use crate::error::{Error, ErrorKind};
+use crate::utils;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> utils::Version {
+        utils::parse_version(version).unwrap()
+    }
+
+    #[test]
+    fn resolves_legacy_suffixes() {
+        let cases = [
+            // Linux-i386 builds stopped with 3.6, x86_64 builds started at 3.1.
+            ("linux-x86", "2.8.12", Ok("Linux-i386")),
+            ("linux-x86", "3.6.3", Ok("Linux-i386")),
+            ("linux-x86", "3.7.0", Err(X86_64_ONLY_STR)),
+            ("linux-x86_64", "3.0.2", Err(I386_ONLY_STR)),
+            ("linux-x86_64", "3.1.0", Ok("Linux-x86_64")),
+            // 64 bit Windows builds started at 3.6.
+            ("windows-x86_64", "3.5.2", Ok("win32-x86")),
+            ("windows-x86_64", "3.6.0", Ok("win64-x64")),
+            ("windows-x86", "3.6.0", Ok("win32-x86")),
+            // 2.x macOS builds are universal.
+            ("macos-x86_64", "2.8.12", Ok("Darwin64-universal")),
+            ("macos-x86_64", "3.0.2", Ok("Darwin64-universal")),
+            ("macos-x86_64", "3.1.0", Ok("Darwin-x86_64")),
+            ("macos-x86", "3.1.0", Err(NO_MAC_X86_STR)),
+            ("linux-aarch64", "3.10.3", Err(NO_ARM_STR)),
+        ];
+
+        for (platform, release, expected) in cases {
+            let platform = Platform::parse(platform).unwrap();
+            assert_eq!(
+                suffix(&version(release), &platform),
+                expected,
+                "resolving {} for {}",
+                release,
+                platform
+            );
+        }
+    }
+
+    #[test]
+    fn only_releases_before_3_11_are_legacy() {
+        assert!(is_legacy(&version("2.8.12")));
+        assert!(is_legacy(&version("3.10.3")));
+        assert!(!is_legacy(&version("3.11.0")));
+    }
+
+    #[test]
+    fn resolves_packages_and_urls() {
+        let linux = Platform::parse("linux-x86").unwrap();
+        let mac = Platform::parse("macos-x86_64").unwrap();
+        let resolve_kind = |platform: &Platform, release: &str, kind: ArchiveKind| {
+            resolve(platform, release, &version(release), Some(kind))
+        };
+
+        let asset = resolve_kind(&linux, "2.8.12", ArchiveKind::TarZ).unwrap();
+        assert_eq!(asset.file_name, "cmake-2.8.12-Linux-i386.tar.Z");
+        assert_eq!(
+            asset.url,
+            "https://cmake.org/files/v2.8/cmake-2.8.12-Linux-i386.tar.Z"
+        );
+
+        let asset = resolve_kind(&linux, "3.5.2", ArchiveKind::Shell).unwrap();
+        assert_eq!(asset.file_name, "cmake-3.5.2-Linux-i386.sh");
+
+        let asset = resolve(&mac, "3.9.6", &version("3.9.6"), None).unwrap();
+        assert_eq!(
+            asset.url,
+            "https://cmake.org/files/v3.9/cmake-3.9.6-Darwin-x86_64.tar.gz"
+        );
+
+        // .tar.Z stopped with 2.x and installers only exist for Linux.
+        let rejected = [
+            resolve_kind(&linux, "3.5.2", ArchiveKind::TarZ),
+            resolve_kind(&mac, "3.5.2", ArchiveKind::Shell),
+            resolve_kind(&mac, "3.5.2", ArchiveKind::Zip),
+        ];
+        for result in rejected {
+            assert_eq!(result.err().unwrap().kind(), ErrorKind::NotFound);
+        }
+    }
+}
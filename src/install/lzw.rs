@@ -0,0 +1,312 @@
+/// Decompresses data produced by the unix `compress` utility (`.Z` files).
+/// Follows the reference implementation including the way codes are read in
+/// groups of eight that are padded whenever the code width changes.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < 3 || data[0] != 0x1f || data[1] != 0x9d {
+        return Err(Error::new(
+            ErrorKind::Archive,
+            "File is not a compressed (.Z) archive.",
+        ));
+    }
+
+    let max_bits = (data[2] & 0x1f) as u32;
+    let block_mode = data[2] & 0x80 != 0;
+    if !(INIT_BITS..=16).contains(&max_bits) {
+        return Err(Error::new(
+            ErrorKind::Archive,
+            format!("Unsupported .Z code width of {} bits.", max_bits),
+        ));
+    }
+
+    let data = &data[3..];
+    let max_max_code = 1usize << max_bits;
+    let total_bits = data.len() * 8;
+
+    let mut prefix = vec![0u16; max_max_code];
+    let mut suffix = vec![0u8; max_max_code];
+    for (code, byte) in suffix.iter_mut().enumerate().take(256) {
+        *byte = code as u8;
+    }
+
+    let mut n_bits = INIT_BITS;
+    let mut max_code = (1usize << n_bits) - 1;
+    let mut free_ent = if block_mode { FIRST } else { 256 };
+    let mut old_code: Option<usize> = None;
+    let mut fin_char = 0u8;
+    let mut pos_bits = 0usize;
+    let mut group_start = 0usize;
+
+    let mut output = Vec::with_capacity(data.len() * 3);
+    let mut stack = Vec::new();
+
+    while pos_bits + n_bits as usize <= total_bits {
+        if free_ent > max_code {
+            pos_bits = align(pos_bits, group_start, n_bits);
+            group_start = pos_bits;
+            n_bits += 1;
+            max_code = if n_bits == max_bits {
+                max_max_code
+            } else {
+                (1usize << n_bits) - 1
+            };
+            continue;
+        }
+
+        let code = read_code(data, pos_bits, n_bits);
+        pos_bits += n_bits as usize;
+
+        let previous = match old_code {
+            Some(previous) => previous,
+            None => {
+                if code >= 256 {
+                    return Err(Error::new(ErrorKind::Archive, CORRUPT_STR));
+                }
+
+                fin_char = code as u8;
+                old_code = Some(code);
+                output.push(fin_char);
+                continue;
+            }
+        };
+
+        if code == CLEAR && block_mode {
+            free_ent = FIRST - 1;
+            pos_bits = align(pos_bits, group_start, n_bits);
+            group_start = pos_bits;
+            n_bits = INIT_BITS;
+            max_code = (1usize << n_bits) - 1;
+            continue;
+        }
+
+        let in_code = code;
+        let mut code = code;
+        stack.clear();
+
+        // The KwKwK case, the code is being defined by this very step.
+        if code >= free_ent {
+            if code > free_ent {
+                return Err(Error::new(ErrorKind::Archive, CORRUPT_STR));
+            }
+
+            stack.push(fin_char);
+            code = previous;
+        }
+
+        while code >= 256 {
+            stack.push(suffix[code]);
+            code = prefix[code] as usize;
+        }
+
+        fin_char = suffix[code];
+        stack.push(fin_char);
+        output.extend(stack.iter().rev());
+
+        if free_ent < max_max_code {
+            prefix[free_ent] = previous as u16;
+            suffix[free_ent] = fin_char;
+            free_ent += 1;
+        }
+
+        old_code = Some(in_code);
+    }
+
+    Ok(output)
+}
+
+/// Skips the rest of the current group of eight codes. Groups are counted
+/// from where the current code width started.
+fn align(pos_bits: usize, group_start: usize, n_bits: u32) -> usize {
+    let group = (n_bits as usize) << 3;
+    let used = (pos_bits - group_start) % group;
+
+    if used == 0 {
+        pos_bits
+    } else {
+        pos_bits + group - used
+    }
+}
+
+fn read_code(data: &[u8], pos_bits: usize, n_bits: u32) -> usize {
+    let byte = pos_bits >> 3;
+    let mut value = 0usize;
+
+    for offset in 0..3 {
+        if let Some(next) = data.get(byte + offset) {
+            value |= (*next as usize) << (offset * 8);
+        }
+    }
+
+    (value >> (pos_bits & 7)) & ((1usize << n_bits) - 1)
+}
+
+const INIT_BITS: u32 = 9;
+const CLEAR: usize = 256;
+const FIRST: usize = 257;
+const CORRUPT_STR: &str = "Compressed (.Z) archive is corrupt.";
+
+use crate::error::{Error, ErrorKind};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compresses like the unix `compress` utility, emitting CLEAR whenever
+    /// the table fills up when `clear` is set.
+    fn compress(data: &[u8], max_bits: u32, clear: bool) -> Vec<u8> {
+        let max_max_code = 1usize << max_bits;
+        let mut table = std::collections::HashMap::new();
+        let mut codes = Vec::new();
+        let mut free_ent = FIRST;
+        let mut word: Option<usize> = None;
+
+        for byte in data {
+            let code = match word {
+                Some(code) => code,
+                None => {
+                    word = Some(*byte as usize);
+                    continue;
+                }
+            };
+
+            if let Some(next) = table.get(&(code, *byte)) {
+                word = Some(*next);
+                continue;
+            }
+
+            codes.push(code);
+            if free_ent < max_max_code {
+                table.insert((code, *byte), free_ent);
+                free_ent += 1;
+            } else if clear {
+                codes.push(CLEAR);
+                table.clear();
+                free_ent = FIRST;
+            }
+            word = Some(*byte as usize);
+        }
+        codes.extend(word);
+
+        // Writes the codes with the widths the decompressor expects.
+        let mut bits = Vec::new();
+        let mut n_bits = INIT_BITS;
+        let mut max_code = (1usize << n_bits) - 1;
+        let mut free_ent = FIRST;
+        let mut group_start = 0;
+
+        for (index, code) in codes.iter().enumerate() {
+            if free_ent > max_code {
+                bits.resize(align(bits.len(), group_start, n_bits), false);
+                group_start = bits.len();
+                n_bits += 1;
+                max_code = if n_bits == max_bits {
+                    max_max_code
+                } else {
+                    (1usize << n_bits) - 1
+                };
+            }
+
+            bits.extend((0..n_bits).map(|bit| code >> bit & 1 == 1));
+
+            if *code == CLEAR {
+                bits.resize(align(bits.len(), group_start, n_bits), false);
+                group_start = bits.len();
+                n_bits = INIT_BITS;
+                max_code = (1usize << n_bits) - 1;
+                free_ent = FIRST - 1;
+            } else if index > 0 && free_ent < max_max_code {
+                free_ent += 1;
+            }
+        }
+
+        let mut output = vec![0x1f, 0x9d, 0x80 | max_bits as u8];
+        output.extend(bits.chunks(8).map(|byte| {
+            byte.iter()
+                .enumerate()
+                .fold(0u8, |value, (bit, set)| value | (*set as u8) << bit)
+        }));
+        output
+    }
+
+    /// The text the fixtures were compressed from.
+    fn lines() -> Vec<u8> {
+        (0..400)
+            .map(|line| format!("line {}: {}\n", line, line * line))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut noise = Vec::new();
+        let mut seed = 7u32;
+        for _ in 0..200_000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            noise.push((seed >> 16) as u8 % 24);
+        }
+
+        let inputs = [
+            b"".to_vec(),
+            b"a".to_vec(),
+            b"TOBEORNOTTOBEORTOBEORNOT".to_vec(),
+            lines(),
+            noise,
+        ];
+        for input in &inputs {
+            for max_bits in [9, 12, 16] {
+                for clear in [false, true] {
+                    let compressed = compress(input, max_bits, clear);
+                    assert_eq!(
+                        decompress(&compressed).unwrap(),
+                        *input,
+                        "{} bytes with {} bits, clear: {}",
+                        input.len(),
+                        max_bits,
+                        clear
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decompresses_growing_code_widths() {
+        // Codes grow from 9 to 11 bits, each width starts a new group.
+        let fixture = include_bytes!("../../tests/fixtures/lines.Z");
+        assert_eq!(decompress(fixture).unwrap(), lines());
+    }
+
+    #[test]
+    fn decompresses_clear_codes() {
+        // 9 bit codes only, the table is cleared each time it fills up.
+        let fixture = include_bytes!("../../tests/fixtures/lines-clear.Z");
+        assert_eq!(fixture[2] & 0x1f, 9);
+        assert_eq!(decompress(fixture).unwrap(), lines());
+    }
+
+    #[test]
+    fn decompresses_codes_defined_by_their_own_step() {
+        // `aaaa...` and `abab...` both read a code before it is in the table.
+        let runs = [0x1f, 0x9d, 0x90, 0x61, 0x02, 0x0a, 0x1c, 0x08];
+        assert_eq!(decompress(&runs).unwrap(), b"aaaaaaaaaa");
+
+        let pairs = [0x1f, 0x9d, 0x90, 0x61, 0xc4, 0x04, 0x1c, 0x28, 0xb0, 0x20];
+        assert_eq!(decompress(&pairs).unwrap(), b"abababababab");
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let cases: [&[u8]; 5] = [
+            b"",
+            b"\x1f\x8b\x08",
+            b"\x1f\x9d\x88",
+            b"\x1f\x9d\x91",
+            // The first code must be a literal byte.
+            b"\x1f\x9d\x90\x00\x03",
+        ];
+
+        for case in cases {
+            let error = decompress(case).err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::Archive, "decompressing {:?}", case);
+        }
+    }
+}
@@ -1,163 +1,807 @@
+mod batch;
+mod build;
+mod cache;
+mod checksum;
+mod du;
+mod extract;
 mod helper_strings;
+mod info;
+mod legacy;
+mod lzw;
+mod platform;
+mod upgrade;
+
+pub use build::build_version;
+pub use cache::{cache_dir, manage_cache};
+pub use du::disk_usage;
+pub use info::show_info;
+pub use upgrade::{outdated, upgrade};
+
+#[derive(Default)]
+struct InstallOptions {
+    versions: Vec<Arc<str>>,
+    version: Option<Arc<str>>,
+    platform: Option<Platform>,
+    download_only: bool,
+    from_file: Option<PathBuf>,
+    sha256: Option<Arc<str>>,
+    package: Option<ArchiveKind>,
+    sources: Option<Vec<Source>>,
+}
+
+fn parse_options(args: &InstallArgs) -> Result<InstallOptions, Error> {
+    Ok(InstallOptions {
+        versions: args
+            .versions
+            .iter()
+            .map(|version| version.as_str().into())
+            .collect(),
+        version: args.version.as_deref().map(Arc::from),
+        platform: parse_platform(args.platform.as_deref())?,
+        download_only: args.download_only,
+        from_file: args.from_file.clone(),
+        sha256: args.sha256.as_deref().map(Arc::from),
+        package: parse_package(args.package.as_deref())?,
+        sources: parse_sources(args.source.as_deref())?,
+    })
+}
+
+fn parse_platform(platform: Option<&str>) -> Result<Option<Platform>, Error> {
+    platform.map(Platform::parse).transpose()
+}
+
+fn parse_package(package: Option<&str>) -> Result<Option<ArchiveKind>, Error> {
+    package.map(ArchiveKind::parse).transpose()
+}
+
+fn parse_sources(specs: Option<&str>) -> Result<Option<Vec<Source>>, Error> {
+    specs.map(sources::parse_list).transpose()
+}
+
+pub fn install_version(args: &InstallArgs, cvm_home: &Path) -> Result<(), Error> {
+    let mut options = parse_options(args)?;
+
+    let sources = match &options.sources {
+        Some(sources) => sources.clone(),
+        None => sources::configured(cvm_home)?,
+    };
+
+    let mut versions = batch::expand_versions(&options.versions, &sources, cvm_home)?;
+    if versions.len() > 1 {
+        return batch::install_many(&versions, &sources, &options, cvm_home);
+    }
+
+    if let Some(version) = versions.pop() {
+        if options.version.is_some() {
+            return Err(Error::new(ErrorKind::Usage, TOO_MANY_ARGS_STR));
+        }
 
-pub fn install_version(args: &Vec<Rc<str>>, cvm_home: &Path) -> Result<(), Rc<str>> {
-    if args.len() > 3 {
-        return Err(TOO_MANY_ARGS_STR.into());
+        options.version = Some(version);
     }
 
-    let releases = cached_releases(cvm_home)?;
-    let tag = get_tag(&releases, args)?;
+    let tag = match &options.from_file {
+        Some(archive) => file_version(archive, &options)?,
+        None => match &options.version {
+            Some(version) if version.starts_with(NIGHTLY_STR) => nightly_tag(version, &sources)?,
+            Some(version) => version.clone(),
+            None => {
+                let releases = match options.sources {
+                    Some(_) => sources::releases(&sources)?,
+                    None => cached_release_info(cvm_home)?,
+                };
+
+                let mut picked = get_tags(&releases, cvm_home)?;
+                if picked.len() > 1 {
+                    return batch::install_many(&picked, &sources, &options, cvm_home);
+                }
+
+                picked.remove(0)
+            }
+        },
+    };
 
     if tag.is_empty() {
         return Ok(());
     }
 
-    // We don't mind if no versions are currently installed.
-    let current = current_version(cvm_home).unwrap_or(Rc::from(""));
-    if *tag == *current {
-        println!("CMake v{}, has already been installed and selected.", tag);
-        return Ok(());
+    if options.from_file.is_none() && !utils::is_nightly(&tag) {
+        check_releases(
+            std::slice::from_ref(&tag),
+            &sources,
+            options.sources.is_none(),
+            cvm_home,
+        )?;
+    }
+
+    let platform = match &options.platform {
+        Some(platform) => platform.clone(),
+        None => Platform::host()?,
+    };
+
+    if options.download_only {
+        return download_only(cvm_home, &tag, &platform, &sources, &options);
+    }
+
+    if !platform.is_host() {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("{} ({})", FOREIGN_PLATFORM_STR, platform),
+        ));
     }
 
-    if !current.is_empty() {
-        let from = cvm_home.join(crate::CVM_BINS).join(crate::CVM_CURRENT_DIR);
-        let to = cvm_home
-            .join(crate::CVM_BINS)
-            .join(format!("cmake-{}", current));
+    let _lock = lock::acquire(cvm_home)?;
 
-        std::fs::rename(from, to).map_err(map_error!("Failed to rename directory. ({})"))?;
+    // We don't mind if no versions are currently installed.
+    let current = current_version(cvm_home).unwrap_or(Arc::from(""));
+    if *tag == *current {
+        report::outcome(
+            &tag,
+            Status::Selected,
+            &format!("CMake v{}, has already been installed and selected.", tag),
+        );
+        return Ok(());
     }
 
     let installed_versions = installed(cvm_home)?;
 
     if is_installed(&tag, &installed_versions) {
-        println!("Version {} is already installed. Switching...", tag);
+        status!("Version {} is already installed. Switching...", tag);
         switch(&tag, cvm_home)?;
+        report::outcome(
+            &tag,
+            Status::Switched,
+            &format!("Switched to CMake v{}.", tag),
+        );
         return Ok(());
     }
 
-    download(cvm_home, &tag)?;
-    set_current_install(cvm_home, &tag)?;
+    // The selected version is left alone until the new one is in place.
+    let origin = match &options.from_file {
+        Some(archive) => install_archive(cvm_home, &tag, archive, options.sha256.as_deref())?,
+        None => download(cvm_home, &tag, &platform, &sources, &options)?,
+    };
+
     set_installed(cvm_home, &tag)?;
+    set_origin(cvm_home, &tag, &origin)?;
+    switch(&tag, cvm_home)?;
+
+    if utils::is_nightly(&tag) {
+        prune::auto_prune(cvm_home)?;
+    }
 
+    report::outcome(
+        &tag,
+        Status::Installed,
+        &format!("Installed and selected CMake v{}.", tag),
+    );
     Ok(())
 }
 
-fn get_tag(releases: &Vec<Rc<str>>, args: &Vec<Rc<str>>) -> Result<Rc<str>, Rc<str>> {
-    if args.len() == 3 {
-        return Ok(args[2].clone());
+/// Makes sure every version is a published release before anything is
+/// downloaded. The release cache is refreshed once when it does not know a
+/// version. Versions older than the releases a source lists, and any version
+/// when the sources cannot be reached, are let through.
+fn check_releases(
+    versions: &[Arc<str>],
+    sources: &[Source],
+    refresh_cache: bool,
+    cvm_home: &Path,
+) -> Result<(), Error> {
+    let cached = cached_releases(cvm_home).unwrap_or_default();
+    let missing = versions
+        .iter()
+        .filter(|version| !is_installed(version, &cached))
+        .collect::<Vec<&Arc<str>>>();
+
+    if missing.is_empty() || cli::offline() {
+        return Ok(());
     }
 
-    if releases.is_empty() {
-        return Err(NO_RELEASES_FOUND_STR.into());
+    status!("Refreshing available versions...");
+    let releases = match sources::releases(sources) {
+        Ok(releases) => releases,
+        Err(error) => {
+            log::warning(&format!(
+                "Could not check that the versions exist. {}",
+                error
+            ));
+            return Ok(());
+        }
+    };
+
+    if refresh_cache {
+        set_cached_releases(cvm_home, &releases)?;
     }
 
-    let mut builder = IList::<Rc<str>>::new("Please select a cmake verson to install:");
+    let releases = releases
+        .into_iter()
+        .map(|release| release.version)
+        .collect::<Vec<Arc<str>>>();
+
+    let oldest = releases.last().cloned().unwrap_or_default();
+    let unknown = missing
+        .into_iter()
+        .filter(|version| !is_installed(version, &releases))
+        .filter(|version| utils::compare_versions(version, &oldest) != Ordering::Less)
+        .map(|version| {
+            let suggestions = utils::suggest(version, &releases);
+            if suggestions.is_empty() {
+                format!("CMake v{} is not a published release.", version)
+            } else {
+                format!(
+                    "CMake v{} is not a published release. Did you mean {}?",
+                    version,
+                    suggestions.join(" / ")
+                )
+            }
+        })
+        .collect::<Vec<String>>();
+
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    Err(Error::new(ErrorKind::NotFound, unknown.join("\n")))
+}
 
-    for i in 0..releases.len() {
-        if i == 11 {
-            break;
+/// Resolves `nightly` to the newest nightly and `nightly-YYYY-MM-DD` to the
+/// nightly built on that day.
+fn nightly_tag(version: &str, sources: &[Source]) -> Result<Arc<str>, Error> {
+    let nightlies = sources::nightlies(sources)?;
+
+    let date = match version.strip_prefix(NIGHTLY_STR) {
+        Some("") => {
+            return nightlies
+                .first()
+                .cloned()
+                .ok_or_else(|| Error::new(ErrorKind::NotFound, NO_NIGHTLIES_STR))
         }
+        Some(date) => date.strip_prefix('-').unwrap_or(""),
+        None => "",
+    };
+
+    let date = date.replace('-', "");
+    if date.len() != 8 || !date.chars().all(|digit| digit.is_ascii_digit()) {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("'{}' is not a nightly. {}", version, NIGHTLY_FORMAT_STR),
+        ));
+    }
+
+    let date = date.parse::<u32>().unwrap_or(0);
+    nightlies
+        .into_iter()
+        .find(|nightly| {
+            utils::parse_nightly(nightly).and_then(|nightly| nightly.date) == Some(date)
+        })
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "No nightly was published on {}.",
+                    &version[NIGHTLY_STR.len() + 1..]
+                ),
+            )
+        })
+}
+
+/// Uses the version given with `--version` or takes it from an archive named
+/// like the official releases. Example: cmake-3.27.9-linux-x86_64.tar.gz
+fn file_version(archive: &Path, options: &InstallOptions) -> Result<Arc<str>, Error> {
+    if let Some(version) = &options.version {
+        return Ok(version.clone());
+    }
+
+    let file_name = archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+
+    file_name
+        .strip_prefix("cmake-")
+        .and_then(|rest| rest.split('-').next())
+        .filter(|version| utils::is_version_number(version))
+        .map(Arc::from)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::Usage,
+                format!(
+                    "Could not detect a CMake version from '{}'. Please pass it with '--version'.",
+                    file_name
+                ),
+            )
+        })
+}
 
-        builder = builder.add_item(releases[i].as_ref(), releases[i].clone());
+/// Asks which releases to install, or takes the project's pin when cvm may
+/// not prompt.
+fn get_tags(releases: &[ReleaseInfo], cvm_home: &Path) -> Result<Vec<Arc<str>>, Error> {
+    if !cli::interactive() {
+        return Ok(vec![pin::required()?]);
     }
 
-    let result = builder.inquire();
+    if releases.is_empty() {
+        return Err(Error::new(ErrorKind::StateCorrupt, NO_RELEASES_FOUND_STR));
+    }
+
+    let mut choices = picker::choices(releases, cvm_home);
+    for choice in choices.iter_mut().filter(|choice| !choice.installed) {
+        choice.size = cache::archive_size(cvm_home, &choice.version);
+    }
 
-    match result {
-        Ok(selected) => Ok(Rc::from(selected)),
-        Err(inq_msg) => match inq_msg {
-            InquiryMessage::CloseRequested => {
-                println!("\nSession was canceled. Exiting...");
-                Ok("".into())
+    picker::pick("Please select cmake versions to install:", &choices, true)
+}
+
+/// Gets the archive from the first source that has it. Also returns the name
+/// of that source.
+fn fetch_asset(
+    sources: &[Source],
+    file_name: &str,
+    default_url: &str,
+    version: &str,
+    bar: &ProgressBar,
+) -> Result<(Vec<u8>, Arc<str>), Error> {
+    let cmake_version = match utils::parse_nightly(version) {
+        Some(nightly) => nightly,
+        None => utils::parse_version(version.split('-').next().unwrap_or(version))?,
+    };
+    let mut errors = Vec::new();
+    let mut kind = ErrorKind::NotFound;
+
+    for source in sources {
+        let result = source
+            .locate(file_name, default_url, &cmake_version)
+            .and_then(|location| match location {
+                Location::Url(url) => fetch(&url, bar),
+                Location::File(path) => {
+                    let data = std::fs::read(path)
+                        .map_err(map_error!(Archive, "Failed to read archive."))?;
+
+                    bar.set_length(data.len() as u64);
+                    bar.set_position(data.len() as u64);
+                    Ok(data)
+                }
+            });
+
+        match result {
+            Ok(data) => return Ok((data, source.to_string().into())),
+            Err(error) => {
+                // Report the kind of the first source, it is the preferred one.
+                if errors.is_empty() {
+                    kind = error.kind();
+                }
+
+                errors.push(format!("{}: {}", source, error));
             }
-            _ => Err("Inquiry failed. exiting session".into()),
-        },
+        }
     }
+
+    Err(Error::new(
+        kind,
+        format!(
+            "Could not get '{}' from any release source.\n{}",
+            file_name,
+            errors.join("\n")
+        ),
+    ))
 }
 
-fn download(cvm_home: &Path, version: &str) -> Result<(), Rc<str>> {
-    let strings = helper_strings::HelperStrings::new(cvm_home, version)?;
+fn fetch(url: &str, bar: &ProgressBar) -> Result<Vec<u8>, Error> {
+    cli::ensure_online(url)?;
 
-    println!("Downloading CMake {}...", version);
-    let byte_data = blocking::Client::new()
-        .get(strings.download_url)
+    let response = blocking::Client::new()
+        .get(url)
         .header(
             header::USER_AGENT,
             format!("cvm {} request", env!("CARGO_PKG_VERSION")),
         )
         .header(header::ACCEPT, "application/vnd.github.v3+json")
         .send()
-        .map_err(map_error!("Failed to request releases from github. ({})"))?
-        .bytes()
-        .map_err(map_error!("Failed to get bytes from request. ({})"))?;
-
-    println!("Writing data to file...");
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(&strings.save_path)
-        .map_err(map_error!("Faild to create save file for download. ({})"))?;
-
-    file.write_all(byte_data.as_ref())
-        .map_err(map_error!("Failed to writed saved data to file. ({})"))?;
-
-    println!("Extracting...");
-    println!(
-        "{} - {}",
-        strings.save_path.to_str().unwrap_or(""),
-        strings.bins_path.to_str().unwrap_or("")
-    );
+        .map_err(map_error!(Network, "Failed to download release."))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("'{}' responded with {}.", url, status),
+        ));
+    }
 
-    let command = format!(
-        r#"tar -xf "{}" -C "{}""#,
-        strings.save_path.to_str().unwrap_or(""),
-        strings.bins_path.to_str().unwrap_or("")
-    );
+    // Error and index pages are sometimes served with a success status.
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+
+    if content_type.starts_with("text/html") {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("'{}' returned a web page instead of an archive.", url),
+        ));
+    }
+
+    bar.set_position(0);
+    if let Some(length) = response.content_length() {
+        bar.set_length(length);
+    }
+
+    let mut data = Vec::new();
+    bar.wrap_read(response)
+        .read_to_end(&mut data)
+        .map_err(map_error!(Network, "Failed to get bytes from request."))?;
 
-    #[cfg(unix)]
-    let program = "sh";
+    Ok(data)
+}
 
-    #[cfg(windows)]
-    let program = "powershell";
+/// Creates a download progress bar. Nothing is drawn when the output is not a
+/// terminal.
+fn progress_bar(name: &str) -> ProgressBar {
+    let style = ProgressStyle::with_template(PROGRESS_TEMPLATE_STR)
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> ");
 
-    let mut child = std::process::Command::new(program)
-        .arg("-c")
-        .arg(&command)
-        .stdout(Stdio::piped())
-        .spawn()
-        .map_err(map_error!("Failed to spawn unzip command. ({})"))?;
+    let bar = ProgressBar::new(0).with_style(style);
+    bar.set_prefix(name.to_string());
+    bar
+}
+
+fn download_only(
+    cvm_home: &Path,
+    version: &str,
+    platform: &Platform,
+    sources: &[Source],
+    options: &InstallOptions,
+) -> Result<(), Error> {
+    let asset = helper_strings::resolve(platform, version, options.package)?;
+
+    let (archive, _) = cached_asset(
+        cvm_home,
+        sources,
+        &asset.file_name,
+        &asset.url,
+        version,
+        options.sha256.as_deref(),
+    )?;
+
+    std::fs::copy(archive, &asset.file_name)
+        .map_err(map_error!(Io, "Failed to write download to file."))?;
+
+    report::outcome(
+        version,
+        Status::Downloaded,
+        &format!(
+            "Saved CMake v{} for {} to '{}'.",
+            version, platform, asset.file_name
+        ),
+    );
+    Ok(())
+}
 
-    child
-        .wait()
-        .map_err(map_error!("Failed to run unzip command. ({})"))?;
+/// Installs a release into `bins/cmake-<version>`. Returns where the archive
+/// came from.
+fn download(
+    cvm_home: &Path,
+    version: &str,
+    platform: &Platform,
+    sources: &[Source],
+    options: &InstallOptions,
+) -> Result<Arc<str>, Error> {
+    let strings = helper_strings::HelperStrings::new(cvm_home, version, platform, options.package)?;
+
+    let (archive, origin) = cached_asset(
+        cvm_home,
+        sources,
+        &strings.file_name,
+        &strings.download_url,
+        version,
+        options.sha256.as_deref(),
+    )?;
+
+    unpack(
+        &archive,
+        strings.kind,
+        &strings.server_name,
+        &strings.bins_path,
+        &format!("cmake-{}", version),
+    )?;
+
+    Ok(origin)
+}
 
-    let from = strings.bins_path.join(strings.server_name);
-    let to = strings.bins_path.join(crate::CVM_CURRENT_DIR);
+/// Downloads every given version into the archive cache without installing.
+pub fn prefetch(args: &PrefetchArgs, cvm_home: &Path) -> Result<(), Error> {
+    let package = parse_package(args.package.as_deref())?;
+
+    let platform = match parse_platform(args.platform.as_deref())? {
+        Some(platform) => platform,
+        None => Platform::host()?,
+    };
+
+    let sources = match parse_sources(args.source.as_deref())? {
+        Some(sources) => sources,
+        None => sources::configured(cvm_home)?,
+    };
+
+    let mut outcomes = Vec::new();
+
+    for version in &args.versions {
+        let version = if version.starts_with(NIGHTLY_STR) {
+            nightly_tag(version, &sources)?
+        } else {
+            version.as_str().into()
+        };
+
+        let asset = helper_strings::resolve(&platform, &version, package)?;
+        cached_asset(
+            cvm_home,
+            &sources,
+            &asset.file_name,
+            &asset.url,
+            &version,
+            None,
+        )?;
+
+        let message = format!("Cached {}.", asset.file_name);
+        if !cli::json() {
+            println!("{}", message);
+        }
 
-    std::fs::rename(from, to).map_err(map_error!("Failed to rename directory. ({})"))?;
+        outcomes.push(Outcome::new(&version, Status::Cached, &message));
+    }
 
-    std::fs::remove_file(strings.save_path)
-        .map_err(map_error!("Failed to cleanup download. ({})"))?;
+    if cli::json() {
+        report::outcomes(&outcomes);
+    }
 
     Ok(())
 }
 
-const NO_RELEASES_FOUND_STR: &'static str = "Seems that we do not have any cached CMake releases.\nTry cleaning with 'cvm remove --all' and try again";
-const TOO_MANY_ARGS_STR: &'static str =
+/// Returns the archive from the cache, downloading and caching it first when
+/// it is not there yet. Archives are verified before they are cached. Also
+/// returns the source the archive came from, `cache` when it was cached.
+fn cached_asset(
+    cvm_home: &Path,
+    sources: &[Source],
+    file_name: &str,
+    default_url: &str,
+    version: &str,
+    sha256: Option<&str>,
+) -> Result<(PathBuf, Arc<str>), Error> {
+    if let Some(archive) = cache::lookup(cvm_home, file_name)? {
+        status!("Using cached {}...", file_name);
+        if let Some(sha256) = sha256 {
+            checksum::verify(&archive, sha256)?;
+        }
+
+        return Ok((archive, CACHE_ORIGIN_STR.into()));
+    }
+
+    status!("Downloading {}...", file_name);
+    let bar = progress_bar(version);
+    let byte_data = fetch_asset(sources, file_name, default_url, version, &bar);
+    bar.finish_and_clear();
+
+    let (byte_data, origin) = byte_data?;
+
+    match sha256 {
+        Some(sha256) => checksum::verify_data(file_name, &byte_data, sha256)?,
+        None => verify_published(sources, file_name, default_url, version, &byte_data)?,
+    }
+
+    Ok((cache::store(cvm_home, file_name, &byte_data)?, origin))
+}
+
+/// Checks a downloaded release archive against the `cmake-<v>-SHA-256.txt`
+/// Kitware publishes next to it, fetched from the same sources. Nightlies
+/// have no such file. A checksum file that cannot be fetched or does not
+/// list the archive is only warned about.
+fn verify_published(
+    sources: &[Source],
+    file_name: &str,
+    default_url: &str,
+    version: &str,
+    data: &[u8],
+) -> Result<(), Error> {
+    if utils::is_nightly(version) {
+        return Ok(());
+    }
+
+    let sums_name = format!("cmake-{}{}", version, checksum::SHA256_SUFFIX);
+    let sums_url = match default_url.rsplit_once('/') {
+        Some((base, _)) => format!("{}/{}", base, sums_name),
+        None => sums_name.clone(),
+    };
+
+    let text = match fetch_asset(
+        sources,
+        &sums_name,
+        &sums_url,
+        version,
+        &ProgressBar::hidden(),
+    ) {
+        Ok((sums, _)) => String::from_utf8_lossy(&sums).into_owned(),
+        Err(error) => {
+            // Going without the checksums is expected while offline.
+            if !cli::offline() {
+                log::warning(&format!("{} '{}'. {}", UNVERIFIED_STR, file_name, error));
+            }
+
+            return Ok(());
+        }
+    };
+
+    match checksum::listed(&text, file_name) {
+        Some(sha256) => checksum::verify_data(file_name, data, &sha256),
+        None => {
+            log::warning(&format!(
+                "{} '{}', '{}' does not list it.",
+                UNVERIFIED_STR, file_name, sums_name
+            ));
+            Ok(())
+        }
+    }
+}
+
+/// Installs a CMake archive that is already on disk. The archive is left in
+/// place.
+fn install_archive(
+    cvm_home: &Path,
+    version: &str,
+    archive: &Path,
+    sha256: Option<&str>,
+) -> Result<Arc<str>, Error> {
+    let file_name = archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+
+    let (server_name, kind) = split_archive_name(file_name)?;
+
+    if let Some(sha256) = sha256 {
+        status!("Verifying checksum...");
+        checksum::verify(archive, sha256)?;
+    }
+
+    unpack(
+        archive,
+        kind,
+        server_name,
+        &cvm_home.join(crate::CVM_BINS),
+        &format!("cmake-{}", version),
+    )?;
+
+    let path = std::fs::canonicalize(archive).unwrap_or_else(|_| archive.to_path_buf());
+    Ok(format!("file:{}", path.display()).into())
+}
+
+/// Splits an archive file name into the name of the directory it is expected
+/// to contain and the kind of archive.
+fn split_archive_name(file_name: &str) -> Result<(&str, ArchiveKind), Error> {
+    if let Some(name) = file_name.strip_suffix(".tar.gz") {
+        Ok((name, ArchiveKind::TarGz))
+    } else if let Some(name) = file_name.strip_suffix(".tgz") {
+        Ok((name, ArchiveKind::TarGz))
+    } else if let Some(name) = file_name.strip_suffix(".tar.Z") {
+        Ok((name, ArchiveKind::TarZ))
+    } else if let Some(name) = file_name.strip_suffix(".sh") {
+        Ok((name, ArchiveKind::Shell))
+    } else if let Some(name) = file_name.strip_suffix(".zip") {
+        Ok((name, ArchiveKind::Zip))
+    } else {
+        Err(Error::new(
+            ErrorKind::Usage,
+            format!(
+                "Unsupported archive '{}'. Expected a .tar.gz, .tar.Z, .sh or .zip file.",
+                file_name
+            ),
+        ))
+    }
+}
+
+/// Extracts an archive into a staging directory next to the installed
+/// versions and moves its top level directory to `bins/<target>`. The staging
+/// directory is removed whether or not extraction succeeded.
+fn unpack(
+    archive: &Path,
+    kind: ArchiveKind,
+    server_name: &str,
+    bins_path: &Path,
+    target: &str,
+) -> Result<(), Error> {
+    let staging = bins_path.join(format!(".extract-{}", server_name));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)
+            .map_err(map_error!(Io, "Failed to clear previous extraction."))?;
+    }
+
+    std::fs::create_dir(&staging)
+        .map_err(map_error!(Io, "Failed to create extraction directory."))?;
+
+    // Installers without a top level directory are extracted into one so the
+    // result looks the same as for the other archives.
+    let destination = if kind.has_top_directory() {
+        staging.clone()
+    } else {
+        staging.join(server_name)
+    };
+
+    status!("Extracting...");
+    let result = std::fs::create_dir_all(&destination)
+        .map_err(map_error!(Io, "Failed to create extraction directory."))
+        .and_then(|_| extract::extract(archive, kind, &destination))
+        .and_then(|_| {
+            let from = root_directory(&staging, server_name)?;
+            let to = bins_path.join(target);
+            std::fs::rename(from, to).map_err(map_error!(Io, "Failed to rename directory."))
+        });
+
+    std::fs::remove_dir_all(&staging)
+        .map_err(map_error!(Io, "Failed to cleanup extraction directory."))?;
+
+    result
+}
+
+/// Finds the directory an archive was packed under. Prefers `server_name` but
+/// accepts any single top level directory for archives that were renamed.
+fn root_directory(staging: &Path, server_name: &str) -> Result<PathBuf, Error> {
+    let expected = staging.join(server_name);
+    if expected.is_dir() {
+        return Ok(expected);
+    }
+
+    let entries = std::fs::read_dir(staging)
+        .map_err(map_error!(Io, "Failed to read extraction directory."))?
+        .filter_map(|entry| entry.ok())
+        .collect::<Vec<_>>();
+
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Ok(entry.path()),
+        _ => Err(Error::new(
+            ErrorKind::Archive,
+            format!(
+                "Archive did not contain the expected directory '{}'.",
+                server_name
+            ),
+        )),
+    }
+}
+
+const PROGRESS_TEMPLATE_STR: &str = "{prefix:>24} [{bar:30}] {bytes}/{total_bytes} {msg}";
+const NIGHTLY_STR: &str = "nightly";
+const UNVERIFIED_STR: &str = "Could not check the published checksum of";
+const CACHE_ORIGIN_STR: &str = "cache";
+const NO_NIGHTLIES_STR: &str = "No nightlies were found in the release sources.";
+const NIGHTLY_FORMAT_STR: &str = "Use 'nightly' or 'nightly-YYYY-MM-DD'.";
+const NO_RELEASES_FOUND_STR: &str = "Seems that we do not have any cached CMake releases.\nTry cleaning with 'cvm remove --all' and try again";
+const TOO_MANY_ARGS_STR: &str =
     "Command 'install' must only contain version to install or be empty for interactive.";
+const FOREIGN_PLATFORM_STR: &str =
+    "Only the host platform can be installed. Use '--download-only' to fetch other platforms.";
 
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::Path;
-use std::process::Stdio;
-use std::rc::Rc;
+use std::cmp::Ordering;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::{blocking, header};
-use term_inquiry::{InquiryMessage, List as IList};
 
-use crate::macros::map_error;
+use crate::cli::{self, InstallArgs, PrefetchArgs};
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::log;
+use crate::macros::{map_error, status};
+use crate::picker;
+use crate::pin;
+use crate::prune;
 use crate::releases::{
-    cached_releases, current_version, installed, is_installed, set_current_install, set_installed,
+    cached_release_info, cached_releases, current_version, installed, is_installed,
+    set_cached_releases, set_installed, set_origin, ReleaseInfo,
 };
+use crate::report::{self, Outcome, Status};
+use crate::sources::{self, Location, Source};
 use crate::switch::switch;
+use crate::utils;
+use helper_strings::ArchiveKind;
+use platform::Platform;
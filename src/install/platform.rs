@@ -0,0 +1,190 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Os {
+    Linux,
+    Mac,
+    Windows,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86,
+    X86_64,
+    Aarch64,
+}
+
+/// Describes the system a CMake release is being resolved for. This is kept
+/// separate from host detection so any platform can be resolved from any host.
+#[derive(Clone)]
+pub struct Platform {
+    pub os: Os,
+    pub arch: Arch,
+    pub os_version: Option<utils::Version>,
+}
+
+impl Platform {
+    /// Detects the platform cvm is currently running on.
+    pub fn host() -> Result<Self, Error> {
+        let os = if cfg!(target_os = "linux") {
+            Os::Linux
+        } else if cfg!(target_os = "macos") {
+            Os::Mac
+        } else if cfg!(windows) {
+            Os::Windows
+        } else {
+            return Err(UNSUPPORTED_HOST_OS_STR.into());
+        };
+
+        let arch = if cfg!(target_arch = "x86_64") {
+            Arch::X86_64
+        } else if cfg!(target_arch = "x86") {
+            Arch::X86
+        } else if cfg!(target_arch = "aarch64") {
+            Arch::Aarch64
+        } else {
+            return Err(UNSUPPORTED_HOST_ARCH_STR.into());
+        };
+
+        Ok(Platform {
+            os,
+            arch,
+            os_version: host_os_version(),
+        })
+    }
+
+    /// Parses a platform given as `<os>-<arch>`. Example: windows-x86_64
+    pub fn parse(platform: &str) -> Result<Self, Error> {
+        let (os, arch) = platform
+            .split_once('-')
+            .ok_or_else(|| usage_error(format!("{} '{}'", INVALID_PLATFORM_STR, platform)))?;
+
+        let os = match os.to_lowercase().as_ref() {
+            "linux" => Os::Linux,
+            "macos" | "darwin" | "osx" => Os::Mac,
+            "windows" | "win" => Os::Windows,
+            _ => {
+                return Err(usage_error(format!(
+                    "Unknown operating system '{}'. {}",
+                    os, PLATFORM_HINT_STR
+                )))
+            }
+        };
+
+        let arch = match arch.to_lowercase().as_ref() {
+            "x86_64" | "x64" | "amd64" => Arch::X86_64,
+            "x86" | "i386" | "i686" => Arch::X86,
+            "aarch64" | "arm64" => Arch::Aarch64,
+            _ => {
+                return Err(usage_error(format!(
+                    "Unknown architecture '{}'. {}",
+                    arch, PLATFORM_HINT_STR
+                )))
+            }
+        };
+
+        Ok(Platform {
+            os,
+            arch,
+            os_version: None,
+        })
+    }
+
+    pub fn is_host(&self) -> bool {
+        match Platform::host() {
+            Ok(host) => host.os == self.os && host.arch == self.arch,
+            Err(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let os = match self.os {
+            Os::Linux => "linux",
+            Os::Mac => "macos",
+            Os::Windows => "windows",
+        };
+
+        let arch = match self.arch {
+            Arch::X86 => "x86",
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+        };
+
+        write!(f, "{}-{}", os, arch)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn host_os_version() -> Option<utils::Version> {
+    let system = System::new();
+    let os_version = system.get_os_version()?;
+
+    utils::parse_version(&os_version).ok()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn host_os_version() -> Option<utils::Version> {
+    None
+}
+
+fn usage_error(message: String) -> Error {
+    Error::new(ErrorKind::Usage, message)
+}
+
+const INVALID_PLATFORM_STR: &str =
+    "Platform must be given as <os>-<arch>, example: windows-x86_64. Got";
+const PLATFORM_HINT_STR: &str =
+    "Supported values are linux, macos, windows and x86, x86_64, aarch64.";
+const UNSUPPORTED_HOST_OS_STR: &str =
+    "The operating system cvm is running on is not supported. Use --platform to pick one.";
+const UNSUPPORTED_HOST_ARCH_STR: &str =
+    "The architecture cvm is running on is not supported. Use --platform to pick one.";
+
+#[cfg(target_os = "macos")]
+use sysinfo::{System, SystemExt};
+
+use std::fmt;
+
+use crate::error::{Error, ErrorKind};
+use crate::utils;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_aliases() {
+        let cases = [
+            ("linux-x86_64", "linux-x86_64"),
+            ("Linux-AMD64", "linux-x86_64"),
+            ("linux-i686", "linux-x86"),
+            ("macos-arm64", "macos-aarch64"),
+            ("darwin-x86_64", "macos-x86_64"),
+            ("osx-aarch64", "macos-aarch64"),
+            ("windows-x64", "windows-x86_64"),
+            ("win-i386", "windows-x86"),
+            ("Windows-ARM64", "windows-aarch64"),
+        ];
+
+        for (given, expected) in cases {
+            let platform = Platform::parse(given).unwrap();
+            assert_eq!(platform.to_string(), expected, "parsing '{}'", given);
+            assert!(platform.os_version.is_none());
+        }
+    }
+
+    #[test]
+    fn parse_rejects_unknown_platforms() {
+        for given in [
+            "",
+            "linux",
+            "linux_x86_64",
+            "solaris-x86_64",
+            "linux-mips",
+            "-x86_64",
+        ] {
+            let error = Platform::parse(given).err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::Usage, "parsing '{}'", given);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+/// An installed version with a newer patch release in its series.
+struct Outdated {
+    version: Arc<str>,
+    latest: Arc<str>,
+    latest_installed: bool,
+    selected: bool,
+}
+
+/// Lists installed versions that have newer patch releases in the release
+/// cache, example: 3.25.1 when 3.25.3 was released.
+pub fn outdated(cvm_home: &Path) -> Result<(), Error> {
+    let installed = installed(cvm_home)?;
+    let releases = stable_releases(&installed, cvm_home);
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+
+    let mut outdated = installed
+        .iter()
+        .filter(|version| utils::is_version_number(version))
+        .filter_map(|version| {
+            let latest = latest_patch(version, &releases)?;
+            (utils::compare_versions(&latest, version) == Ordering::Greater).then(|| Outdated {
+                version: version.clone(),
+                latest_installed: is_installed(&latest, &installed),
+                latest,
+                selected: *version == current,
+            })
+        })
+        .collect::<Vec<Outdated>>();
+
+    outdated.sort_by(|a, b| utils::compare_versions(&b.version, &a.version));
+
+    if cli::json() {
+        let outdated = outdated
+            .iter()
+            .map(|outdated| {
+                json!({
+                    "version": outdated.version.as_ref(),
+                    "latest": outdated.latest.as_ref(),
+                    "latest_installed": outdated.latest_installed,
+                    "selected": outdated.selected,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        report::print_json(&json!({ "outdated": outdated }));
+        return Ok(());
+    }
+
+    if outdated.is_empty() {
+        println!("{}", UP_TO_DATE_STR);
+        return Ok(());
+    }
+
+    for outdated in &outdated {
+        let mut notes = Vec::new();
+        if outdated.selected {
+            notes.push("selected");
+        }
+        if outdated.latest_installed {
+            notes.push("latest installed");
+        }
+
+        println!(
+            "    {:<16} → {:<16} {}",
+            outdated.version,
+            outdated.latest,
+            notes.join(", ")
+        );
+    }
+
+    println!("Use 'cvm upgrade --all' to install the newest patch releases.");
+    Ok(())
+}
+
+/// Installs the newest patch release of a series, of every installed series
+/// with --all, or of the selected version's series. Optionally moves the
+/// selection and the project pin over and removes the superseded versions.
+pub fn upgrade(args: &UpgradeArgs, cvm_home: &Path) -> Result<(), Error> {
+    let installed = installed(cvm_home)?;
+    let releases = stable_releases(&installed, cvm_home);
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+
+    let mut series = match (&args.series, args.all) {
+        (Some(series), _) => vec![utils::series(series).ok_or_else(|| {
+            Error::new(
+                ErrorKind::Usage,
+                format!("'{}' {}", series, INVALID_SERIES_STR),
+            )
+        })?],
+        (None, true) => installed
+            .iter()
+            .filter(|version| utils::is_version_number(version))
+            .filter_map(|version| utils::series(version))
+            .collect(),
+        (None, false) if utils::is_version_number(&current) => {
+            utils::series(&current).into_iter().collect()
+        }
+        (None, false) => return Err(Error::new(ErrorKind::Usage, NO_SERIES_STR)),
+    };
+    series.sort_by(|a, b| b.cmp(a));
+    series.dedup();
+
+    // The newest release of each series and the installed versions it
+    // supersedes.
+    let mut upgrades = Vec::new();
+    for (major, minor) in series {
+        let latest = match latest_patch(&format!("{}.{}.0", major, minor), &releases) {
+            Some(latest) => latest,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "There are no releases of {}.{} in the release cache.",
+                        major, minor
+                    ),
+                ))
+            }
+        };
+
+        let superseded = installed
+            .iter()
+            .filter(|version| utils::is_version_number(version))
+            .filter(|version| utils::series(version) == Some((major, minor)))
+            .filter(|version| utils::compare_versions(version, &latest) == Ordering::Less)
+            .cloned()
+            .collect::<Vec<Arc<str>>>();
+
+        upgrades.push((latest, superseded));
+    }
+
+    let missing = upgrades
+        .iter()
+        .map(|(latest, _)| latest.clone())
+        .filter(|latest| !is_installed(latest, &installed))
+        .collect::<Vec<Arc<str>>>();
+
+    let mut outcomes = Vec::new();
+    if !missing.is_empty() {
+        let sources = sources::configured(cvm_home)?;
+        outcomes = batch::install_all(&missing, &sources, &InstallOptions::default(), cvm_home)?;
+    }
+
+    for (latest, superseded) in &upgrades {
+        if !missing.contains(latest) && !superseded.is_empty() {
+            outcomes.push(Outcome::new(
+                latest,
+                Status::AlreadyInstalled,
+                "already installed",
+            ));
+        }
+    }
+
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.status, Status::Failed))
+        .map(|outcome| outcome.version.clone())
+        .collect::<Vec<Arc<str>>>();
+
+    // Versions that failed to install do not supersede anything.
+    upgrades.retain(|(latest, _)| !failed.contains(latest));
+
+    let pin = if args.pin { pin::find()? } else { None };
+
+    // install_all holds the lock itself, the rest of the upgrade takes it now.
+    let _lock = lock::acquire(cvm_home)?;
+    let mut current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+
+    for (latest, superseded) in &upgrades {
+        if args.switch && superseded.contains(&current) {
+            status!("Switching to v{}...", latest);
+            switch::switch(latest, cvm_home)?;
+            outcomes.push(Outcome::new(
+                latest,
+                Status::Switched,
+                &format!("switched from v{}", current),
+            ));
+            current = latest.clone();
+        }
+
+        if let Some(pin) = &pin {
+            let pinned = utils::is_version_number(&pin.version)
+                && utils::series(&pin.version) == utils::series(latest)
+                && utils::compare_versions(&pin.version, latest) == Ordering::Less;
+
+            if pinned {
+                pin::update(pin, latest)?;
+                outcomes.push(Outcome::new(
+                    latest,
+                    Status::Pinned,
+                    &format!("pinned in '{}'", pin.file.display()),
+                ));
+            }
+        }
+
+        if args.remove {
+            for version in superseded {
+                if *version == current {
+                    log::warning(&format!(
+                        "Keeping v{} since it is selected. Pass --switch to move the selection.",
+                        version
+                    ));
+                    continue;
+                }
+
+                status!("Removing v{}...", version);
+                remove_installed(cvm_home, version)?;
+                outcomes.push(Outcome::new(
+                    version,
+                    Status::Removed,
+                    &format!("removed, superseded by v{}", latest),
+                ));
+            }
+        }
+    }
+
+    if cli::json() {
+        report::outcomes(&outcomes);
+    } else if outcomes.is_empty() {
+        println!("{}", UP_TO_DATE_STR);
+    } else {
+        batch::print_summary(&outcomes);
+    }
+
+    report::failures(&mut outcomes, missing.len())?;
+
+    if !cli::json() {
+        if let Some((latest, _)) = upgrades
+            .iter()
+            .find(|(_, superseded)| superseded.contains(&current))
+        {
+            println!("Use 'cvm switch {}' to select the upgrade.", latest);
+        }
+    }
+
+    Ok(())
+}
+
+/// Stable releases from the release cache and the installed versions.
+fn stable_releases(installed: &[Arc<str>], cvm_home: &Path) -> Vec<Arc<str>> {
+    let mut releases = cached_releases(cvm_home).unwrap_or_default();
+    releases.extend(installed.iter().cloned());
+
+    releases.retain(|version| utils::is_version_number(version));
+    releases.sort_by(|a, b| utils::compare_versions(a, b));
+    releases.dedup();
+    releases
+}
+
+/// The newest of `releases` in the series of `version`.
+fn latest_patch(version: &str, releases: &[Arc<str>]) -> Option<Arc<str>> {
+    let series = utils::series(version)?;
+
+    releases
+        .iter()
+        .filter(|release| utils::series(release) == Some(series))
+        .max_by(|a, b| utils::compare_versions(a, b))
+        .cloned()
+}
+
+const UP_TO_DATE_STR: &str = "Everything is up to date.";
+const NO_SERIES_STR: &str = "No release is selected. Pass a series, example: 3.28, or --all.";
+const INVALID_SERIES_STR: &str = "is not a series, example: 3.28";
+
+use std::cmp::Ordering;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use super::{batch, InstallOptions};
+use crate::cli::{self, UpgradeArgs};
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::log;
+use crate::macros::status;
+use crate::pin;
+use crate::releases::{cached_releases, current_version, installed, is_installed};
+use crate::remove::remove_installed;
+use crate::report::{self, Outcome, Status};
+use crate::sources;
+use crate::switch;
+use crate::utils;
@@ -1,24 +1,40 @@
-pub fn install_or_switch(version: &str, cvm_home: &Path) -> Result<(), Rc<str>> {
+pub fn install_or_switch(version: &str, cvm_home: &Path) -> Result<(), Error> {
     // We don't mind if no versions are currently installed.
     let current = current_version(cvm_home).unwrap_or("".into());
 
     if current.as_ref() == version {
-        println!(
-            "Version already installed and set. If not working make sure PATH is set correctly"
+        report::outcome(
+            version,
+            Status::Selected,
+            "Version already installed and set. If not working make sure PATH is set correctly",
         );
         return Ok(());
     }
 
-    let args = vec!["cvm".into(), "install".into(), version.into()];
+    let args = InstallArgs {
+        versions: vec![version.to_string()],
+        platform: None,
+        download_only: false,
+        from_file: None,
+        version: None,
+        sha256: None,
+        package: None,
+        source: None,
+    };
 
     install::install_version(&args, cvm_home)?;
+
+    let _lock = lock::acquire(cvm_home)?;
     set_current_install(cvm_home, version)?;
 
     Ok(())
 }
 
 use std::path::Path;
-use std::rc::Rc;
 
+use crate::cli::InstallArgs;
+use crate::error::Error;
 use crate::install;
+use crate::lock;
 use crate::releases::{current_version, set_current_install};
+use crate::report::{self, Status};
@@ -1,33 +1,277 @@
-pub fn list_releases(args: &Vec<Rc<str>>, cvm_home: &Path) -> Result<(), Rc<str>> {
-    let releases = cached_releases(cvm_home)?;
+pub fn list_releases(args: &ListArgs, cvm_home: &Path) -> Result<(), Error> {
+    let source = match &args.source {
+        Some(specs) => Some(sources::parse_list(specs)?),
+        None => None,
+    };
 
     // We dont mind if there are no current versions installed.
-    let current = current_version(cvm_home).unwrap_or(Rc::from(""));
+    let current = current_version(cvm_home).unwrap_or(Arc::from(""));
 
     // We dont mind if none are installed.
-    let installed = installed(cvm_home).unwrap_or(Vec::new());
+    let installed = installed(cvm_home).unwrap_or_default();
+    let linked = linked(cvm_home).unwrap_or_default();
+
+    let mut releases = if args.installed {
+        installed_releases(&installed, cvm_home)
+    } else if args.nightlies {
+        let sources = match source {
+            Some(source) => source,
+            None => sources::configured(cvm_home)?,
+        };
 
-    if args.len() == 2 {
-        println!("Currently available releases (last 10):");
-        for i in 0..10 {
-            print_release(&releases[i], &current, &installed);
+        sources::nightlies(&sources)?
+            .into_iter()
+            .map(ReleaseInfo::new)
+            .collect()
+    } else {
+        match &source {
+            Some(source) => sources::releases(source)?,
+            None => cached_release_info(cvm_home)?,
         }
+    };
+
+    if args.available {
+        releases.retain(|release| !is_installed(&release.version, &installed));
+    }
+
+    if let Some(series) = &args.series {
+        let pattern = parse_series(series)?;
+        releases.retain(|release| in_series(&pattern, &release.version));
+    }
+
+    if let Some(since) = &args.since {
+        // `3.25` stands for 3.25.0, compare_versions only orders full versions.
+        let since = utils::parse_version(since)?;
+        let since = format!("{}.{}.{}", since.major, since.minor, since.patch);
+        releases.retain(|release| utils::compare_versions(&release.version, &since).is_ge());
+    }
 
+    releases.sort_by(|a, b| utils::compare_versions(&b.version, &a.version));
+
+    if args.grouped {
+        let mut groups = group(&releases, &installed);
+        if let Some(limit) = args.limit {
+            groups.truncate(limit);
+        }
+
+        print_groups(&groups, &current);
         return Ok(());
     }
 
-    if args.len() >= 3 && args[2].as_ref() != "--all" && args[2].as_ref() != "-a" {
-        return Err(INVALID_ARGS_STR.into());
+    let default_view = !args.all
+        && !args.installed
+        && !args.available
+        && !args.nightlies
+        && args.series.is_none()
+        && args.since.is_none();
+
+    match args.limit {
+        Some(limit) => releases.truncate(limit),
+        None if default_view => releases.truncate(DEFAULT_LIMIT),
+        None => {}
     }
 
-    for release in releases {
-        print_release(&release, &current, &installed);
+    if cli::json() {
+        print_json(&releases, &current, &installed, &linked);
+        return Ok(());
+    }
+
+    if args.installed {
+        println!("Installed versions:");
+    } else if args.nightlies {
+        println!("Available nightlies:");
+    } else if default_view {
+        println!("Currently available releases (last {}):", releases.len());
+    }
+
+    if releases.is_empty() {
+        println!("{}", NO_MATCHES_STR);
+    }
+
+    for release in &releases {
+        if args.installed {
+            let text = if is_linked(&release.version, &linked) {
+                "(linked)"
+            } else {
+                ""
+            };
+
+            print_installed(&release.version, text, release.version == current);
+        } else {
+            print_release(&release.version, &current, &installed);
+        }
     }
 
     Ok(())
 }
 
-fn print_release(release: &str, current: &str, installed: &Vec<Rc<str>>) {
+/// A minor series with its newest listed release.
+struct Group {
+    series: (i32, i32),
+    latest: Arc<str>,
+    installed: Vec<Arc<str>>,
+}
+
+/// Groups sorted `releases` by minor series, newest series first. Versions
+/// that are not releases, such as linked builds, are left out.
+fn group(releases: &[ReleaseInfo], installed: &[Arc<str>]) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+
+    for release in releases {
+        let series = match utils::series(&release.version) {
+            Some(series) => series,
+            None => continue,
+        };
+
+        if groups.iter().any(|group| group.series == series) {
+            continue;
+        }
+
+        let mut installed = installed
+            .iter()
+            .filter(|version| utils::series(version) == Some(series))
+            .cloned()
+            .collect::<Vec<Arc<str>>>();
+        installed.sort_by(|a, b| utils::compare_versions(b, a));
+
+        groups.push(Group {
+            series,
+            latest: release.version.clone(),
+            installed,
+        });
+    }
+
+    groups
+}
+
+fn print_groups(groups: &[Group], current: &str) {
+    if cli::json() {
+        let series = groups
+            .iter()
+            .map(|group| {
+                json!({
+                    "series": format!("{}.{}", group.series.0, group.series.1),
+                    "latest": group.latest.as_ref(),
+                    "installed": group.installed,
+                    "selected": group.installed.iter().any(|version| version.as_ref() == current),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        report::print_json(&json!({ "series": series }));
+        return;
+    }
+
+    if groups.is_empty() {
+        println!("{}", NO_MATCHES_STR);
+    }
+
+    for group in groups {
+        let series = format!("{}.{}", group.series.0, group.series.1);
+        let installed = group
+            .installed
+            .iter()
+            .map(|version| {
+                if version.as_ref() == current {
+                    format!("{} (selected)", version)
+                } else {
+                    version.to_string()
+                }
+            })
+            .collect::<Vec<String>>();
+
+        let text = if installed.is_empty() {
+            String::new()
+        } else {
+            format!("(installed: {})", installed.join(", "))
+        };
+
+        AnsiBuilder::new()
+            .text(&format!("    {:<8} {:<16} ", series, group.latest))
+            .color()
+            .fg()
+            .green()
+            .text(&text)
+            .reset_attributes()
+            .println();
+    }
+}
+
+/// Installed versions with publish dates from the release cache.
+fn installed_releases(installed: &[Arc<str>], cvm_home: &Path) -> Vec<ReleaseInfo> {
+    let cached = cached_release_info(cvm_home).unwrap_or_default();
+
+    installed
+        .iter()
+        .map(|version| {
+            cached
+                .iter()
+                .find(|release| release.version == *version)
+                .cloned()
+                .unwrap_or_else(|| ReleaseInfo::new(version.clone()))
+        })
+        .collect()
+}
+
+/// Splits a series such as `3.28` or `3.2x`. A trailing `x` stands for any
+/// digit.
+fn parse_series(series: &str) -> Result<Vec<&str>, Error> {
+    let parts = series.split('.').collect::<Vec<&str>>();
+    let valid = parts.len() <= 2
+        && parts.iter().all(|part| {
+            let digits = part.trim_end_matches('x');
+            !part.is_empty() && digits.chars().all(|letter| letter.is_ascii_digit())
+        });
+
+    if !valid {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("'{}' {}", series, INVALID_SERIES_STR),
+        ));
+    }
+
+    Ok(parts)
+}
+
+fn in_series(pattern: &[&str], version: &str) -> bool {
+    let (major, minor) = match utils::series(version) {
+        Some(series) => series,
+        None => return false,
+    };
+
+    let numbers = [major.to_string(), minor.to_string()];
+
+    pattern.iter().zip(numbers.iter()).all(|(part, number)| {
+        let digits = part.trim_end_matches('x');
+        digits.is_empty() || (number.len() == part.len() && number.starts_with(digits))
+    })
+}
+
+/// Prints `{"versions": [...]}` with one entry per release.
+fn print_json(
+    releases: &[ReleaseInfo],
+    current: &str,
+    installed: &[Arc<str>],
+    linked: &[(Arc<str>, PathBuf)],
+) {
+    let versions = releases
+        .iter()
+        .map(|release| {
+            json!({
+                "version": release.version.as_ref(),
+                "installed": is_installed(&release.version, installed),
+                "selected": release.version.as_ref() == current,
+                "linked": is_linked(&release.version, linked),
+                "prerelease": utils::is_prerelease(&release.version),
+                "published": release.published.as_deref(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    report::print_json(&json!({ "versions": versions }));
+}
+
+fn print_release(release: &str, current: &str, installed: &[Arc<str>]) {
     let is_installed = is_installed(release, installed);
     let text = if release == current && is_installed {
         "(installed | selected)"
@@ -47,12 +291,38 @@ fn print_release(release: &str, current: &str, installed: &Vec<Rc<str>>) {
         .println();
 }
 
-const INVALID_ARGS_STR: &'static str =
-    "Option 'list' must have expected optional flags: --all or -a";
+fn print_installed(version: &str, text: &str, selected: bool) {
+    let selected = if selected { "(selected)" } else { "" };
+
+    AnsiBuilder::new()
+        .text(&format!("    {} ", version))
+        .color()
+        .fg()
+        .green()
+        .text(selected)
+        .color()
+        .fg()
+        .blue()
+        .text(text)
+        .reset_attributes()
+        .println();
+}
+
+const DEFAULT_LIMIT: usize = 10;
+const NO_MATCHES_STR: &str = "    No versions match.";
+const INVALID_SERIES_STR: &str = "is not a release series. Use for example 3.28 or 3.2x.";
 
-use std::path::Path;
-use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use ansi_builder::AnsiBuilder;
+use serde_json::json;
 
-use crate::releases::{cached_releases, current_version, installed, is_installed};
+use crate::cli::{self, ListArgs};
+use crate::error::{Error, ErrorKind};
+use crate::releases::{
+    cached_release_info, current_version, installed, is_installed, is_linked, linked, ReleaseInfo,
+};
+use crate::report;
+use crate::sources;
+use crate::utils;
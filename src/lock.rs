@@ -0,0 +1,61 @@
+/// Exclusive access to the state in the cvm home. Held while versions are
+/// extracted and registered so concurrent cvm processes do not interleave
+/// writes to bins and the tracking files. Released when dropped.
+/// Waiting for another process gives up after a few minutes.
+pub struct StateLock {
+    file: File,
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        // The lock also goes away with the file handle, failing here is fine.
+        let _ = self.file.unlock();
+    }
+}
+
+pub fn acquire(cvm_home: &Path) -> Result<StateLock, Error> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(cvm_home.join(LOCK_FILE))
+        .map_err(map_error!(Io, "Failed to open cvm lock file."))?;
+
+    if file.try_lock_exclusive().is_ok() {
+        return Ok(StateLock { file });
+    }
+
+    status!("Waiting for another cvm process to finish...");
+    let started = Instant::now();
+
+    while started.elapsed() < LOCK_TIMEOUT {
+        thread::sleep(LOCK_RETRY);
+
+        if file.try_lock_exclusive().is_ok() {
+            return Ok(StateLock { file });
+        }
+    }
+
+    Err(Error::new(
+        ErrorKind::LockTimeout,
+        format!(
+            "Another cvm process held the lock on '{}' for over {} seconds.",
+            cvm_home.to_str().unwrap_or(""),
+            LOCK_TIMEOUT.as_secs()
+        ),
+    ))
+}
+
+pub const LOCK_FILE: &str = "cvm.lock";
+const LOCK_RETRY: Duration = Duration::from_millis(100);
+const LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use fs2::FileExt;
+
+use crate::error::{Error, ErrorKind};
+use crate::macros::{map_error, status};
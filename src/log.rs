@@ -1,4 +1,9 @@
 pub fn error(msg: &str) {
+    if crate::cli::json() {
+        eprintln!("error: {}", msg);
+        return;
+    }
+
     AnsiBuilder::new()
         .color()
         .fg()
@@ -10,7 +15,32 @@ pub fn error(msg: &str) {
         .println();
 }
 
+/// Prints an error that led to the one printed before it.
+pub fn cause(msg: &str) {
+    if crate::cli::json() {
+        eprintln!("  caused by: {}", msg);
+        return;
+    }
+
+    AnsiBuilder::new()
+        .text("  ")
+        .color()
+        .fg()
+        .bright_red()
+        .text("caused by")
+        .reset_attributes()
+        .text(": ")
+        .text(msg)
+        .println();
+}
+
 pub fn warning(msg: &str) {
+    // Standard output only carries the JSON document with --json.
+    if crate::cli::json() {
+        eprintln!("warning: {}", msg);
+        return;
+    }
+
     AnsiBuilder::new()
         .color()
         .fg()
@@ -23,6 +53,10 @@ pub fn warning(msg: &str) {
 }
 
 pub fn info(msg: &str) {
+    if crate::cli::quiet() {
+        return;
+    }
+
     AnsiBuilder::new()
         .color()
         .fg()
@@ -1,7 +1,19 @@
+/// Wraps an error into a cvm error of the given kind, keeping it as the
+/// cause: `.map_err(map_error!(Io, "Failed to read file."))`.
 macro_rules! map_error {
-    ($msg: expr) => {
-        |error| Rc::from(format!($msg, error))
+    ($kind: ident, $msg: expr) => {
+        |error| crate::error::Error::new(crate::error::ErrorKind::$kind, $msg).caused_by(error)
+    };
+}
+
+/// Prints progress messages. Silenced with --quiet.
+macro_rules! status {
+    ($($arg: tt)*) => {
+        if !crate::cli::quiet() {
+            println!($($arg)*);
+        }
     };
 }
 
 pub(crate) use map_error;
+pub(crate) use status;
@@ -1,53 +1,107 @@
-mod help;
+mod adopt;
+mod cli;
+mod completions;
+mod config;
+mod error;
 mod install;
 mod install_or_switch;
 mod list;
+mod lock;
 mod log;
 mod macros;
+mod picker;
+mod pin;
+mod prune;
 mod releases;
 mod remove;
+mod report;
 mod setup;
+mod sources;
 mod switch;
 mod utils;
-mod version;
+mod which;
 
-fn process_arguments(args: &Vec<Rc<str>>, cvm_home: &Path) -> Result<(), Rc<str>> {
-    match args[1].as_ref() {
-        "current" => {
-            let version = releases::current_version(&cvm_home)?;
-            println!("Currently selected CMake version is v{}", version);
+fn process_command(command: &Command, cvm_home: &Path) -> Result<(), Error> {
+    match command {
+        Command::Current => {
+            let version = releases::current_version(cvm_home)?;
+            if cli::json() {
+                let prefix = releases::install_prefix(cvm_home, &version)?;
+                report::print_json(&serde_json::json!({
+                    "version": version.as_ref(),
+                    "source": "global",
+                    "bin": utils::bin_dir(&prefix),
+                }));
+            } else {
+                println!("Currently selected CMake version is v{}", version);
+            }
+        }
+        Command::Info(args) => {
+            install::show_info(args, cvm_home)?;
+        }
+        Command::Which(args) => {
+            which::which(args, cvm_home)?;
+        }
+        Command::Path(args) => {
+            which::path(args, cvm_home)?;
+        }
+        Command::Outdated => {
+            install::outdated(cvm_home)?;
+        }
+        Command::Upgrade(args) => {
+            install::upgrade(args, cvm_home)?;
+        }
+        Command::Du(args) => {
+            install::disk_usage(args, cvm_home)?;
+        }
+        Command::Adopt(args) => {
+            adopt::adopt(args, cvm_home)?;
         }
-        "list" => {
-            list::list_releases(&args, &cvm_home)?;
+        Command::Link(args) => {
+            adopt::link(args, cvm_home)?;
         }
-        "install" => {
-            install::install_version(&args, &cvm_home)?;
+        Command::Completions(args) => {
+            completions::print_script(args.shell);
         }
-        "remove" => {
-            remove::remove(&args, &cvm_home)?;
+        Command::Complete(args) => {
+            completions::complete(args, cvm_home)?;
         }
-        "switch" => {
-            switch::switch_version(&args, &cvm_home)?;
+        Command::Cache(command) => {
+            install::manage_cache(command, cvm_home)?;
         }
-        "--help" | "-h" => {
-            help::dislay_help();
+        Command::Build(args) => {
+            install::build_version(args, cvm_home)?;
         }
-        "--version" | "-v" => {
-            version::display_version(&args);
+        Command::List(args) => {
+            list::list_releases(args, cvm_home)?;
         }
-        x if utils::is_version_number(&x) => {
-            if args.len() != 2 {
+        Command::Install(args) => {
+            install::install_version(args, cvm_home)?;
+        }
+        Command::Prune(args) => {
+            prune::prune(args, cvm_home)?;
+        }
+        Command::Protect(args) => {
+            prune::protect(args, cvm_home)?;
+        }
+        Command::Remove(args) => {
+            remove::remove(args, cvm_home)?;
+        }
+        Command::Switch(args) => {
+            switch::switch_version(args.version.as_deref(), cvm_home)?;
+        }
+        Command::Version(args) if utils::is_version_number(&args[0]) => {
+            if args.len() != 1 {
                 log::warning(IGNORING_EXTRA_ARGS_STR);
             }
 
-            install_or_switch::install_or_switch(&args[1], &cvm_home)?;
+            install_or_switch::install_or_switch(&args[0], cvm_home)?;
         }
-        _ => {
-            let mut msg = UNSUPPORTED_ARG_STR.to_string();
-            args.iter()
-                .for_each(|arg| msg.push_str(&format!("{} ", arg.as_ref())));
-
-            return Err(msg.into());
+        Command::Version(args) => {
+            return Err(Error::new(
+                ErrorKind::Usage,
+                format!("'{}' {}", args[0], UNSUPPORTED_ARG_STR),
+            ));
         }
     }
 
@@ -55,48 +109,96 @@ fn process_arguments(args: &Vec<Rc<str>>, cvm_home: &Path) -> Result<(), Rc<str>
 }
 
 fn main() {
-    let args: Vec<Rc<str>> = std::env::args().map(|arg| arg.into()).collect();
-
-    if args.len() == 1 {
-        log::error(NO_ARGS_DETECTED_STR);
-        return;
-    }
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => exit_on_usage_error(error),
+    };
+    cli::set_globals(&cli);
 
-    let cvm_home = match std::env::var(HOME_ENV_STR) {
-        Ok(path) => Path::new(&path).join(CVM_DIR),
-        Err(error) => {
-            log::error(&format!("Failed to find $HOME path. ({})", error));
-            return;
+    let cvm_home = match (&cli.home, std::env::var(HOME_ENV_STR)) {
+        (Some(home), _) => home.clone(),
+        (None, Ok(path)) => Path::new(&path).join(CVM_DIR),
+        (None, Err(error)) => {
+            let message = "Failed to find $HOME path.";
+            exit_with_error(&Error::new(ErrorKind::Other, message).caused_by(error));
         }
     };
 
-    if let Err(error) = setup::setup_cvm(&cvm_home) {
-        log::error(error.as_ref());
-        log::error(SETUP_FAILURE_STR);
-        return;
+    // Completions run on every tab press and must not create state or use the
+    // network.
+    let needs_setup = !matches!(cli.command, Command::Completions(_) | Command::Complete(_));
+
+    if needs_setup {
+        if let Err(error) = setup::setup_cvm(&cvm_home) {
+            let error = Error::new(error.kind(), SETUP_FAILURE_STR).caused_by(error);
+            exit_with_error(&error);
+        }
     }
 
-    if let Err(error) = process_arguments(&args, &cvm_home) {
-        log::error(error.as_ref());
+    if let Err(error) = process_command(&cli.command, &cvm_home) {
+        exit_with_error(&error);
     }
 }
 
-const CVM_BINS: &'static str = "bins";
-const CVM_DIR: &'static str = ".cvm";
-const CVM_CACHE: &'static str = "cvm_cache";
-const CVM_INSTALLED: &'static str = "cvm_installed";
-const CVM_CURRENT_DIR: &'static str = "current";
-const CVM_CURRENT_FILE: &'static str = "cvm_current";
+/// Prints the error and exits with the code of its kind. The causes are only
+/// printed with --verbose.
+fn exit_with_error(error: &Error) -> ! {
+    if cli::json() {
+        report::error(error);
+    } else if cli::verbose() {
+        log::error(error.message());
+        for cause in error.causes() {
+            log::cause(&cause);
+        }
+    } else {
+        log::error(&error.to_string());
+    }
+
+    std::process::exit(error.kind().exit_code())
+}
+
+/// Lets clap print usage errors, help and the version. Usage errors are
+/// reported as JSON when --json was given.
+fn exit_on_usage_error(error: clap::Error) -> ! {
+    let wants_json = std::env::args().any(|arg| arg == "--json");
+    if wants_json && error.use_stderr() {
+        let rendered = error.render().to_string();
+        let message = rendered.lines().next().unwrap_or("");
+        let message = message.strip_prefix("error: ").unwrap_or(message);
+        report::error(&Error::new(ErrorKind::Usage, message));
+        std::process::exit(error.exit_code());
+    }
+
+    error.exit()
+}
+
+const CVM_ARCHIVES: &str = "archives";
+const CVM_BINS: &str = "bins";
+const CVM_DIR: &str = ".cvm";
+const CVM_CACHE: &str = "cvm_cache";
+const CVM_CONFIG: &str = "cvm_config";
+const CVM_INSTALLED: &str = "cvm_installed";
+const CVM_LINKED: &str = "cvm_linked";
+const CVM_LOGS: &str = "logs";
+const CVM_MARKER: &str = ".cvm-home";
+const CVM_ORIGINS: &str = "cvm_origins";
+const CVM_PROTECTED: &str = "cvm_protected";
+const CVM_USED: &str = "cvm_used";
+const CVM_CURRENT_DIR: &str = "current";
+const CVM_CURRENT_FILE: &str = "cvm_current";
 #[cfg(unix)]
-const HOME_ENV_STR: &'static str = "HOME";
+const HOME_ENV_STR: &str = "HOME";
 #[cfg(windows)]
-const HOME_ENV_STR: &'static str = "USERPROFILE";
-const IGNORING_EXTRA_ARGS_STR: &'static str =
+const HOME_ENV_STR: &str = "USERPROFILE";
+const IGNORING_EXTRA_ARGS_STR: &str =
     "There are other arguments detected after cmake version.\nIgnoring arguments after version.";
-const NO_ARGS_DETECTED_STR: &'static str =
-    "Did not get any options for command. Execute 'cvm --help' for options";
-const SETUP_FAILURE_STR: &'static str = "Failed to set up cvm for use.";
-const UNSUPPORTED_ARG_STR: &'static str = "The first argument does not match whith any option we support.\nPlease use 'cvm --help' to view all possible options.\nGiven options: ";
+const SETUP_FAILURE_STR: &str = "Failed to set up cvm for use.";
+const UNSUPPORTED_ARG_STR: &str =
+    "is not a command or a CMake version.\nPlease use 'cvm --help' to view all possible options.";
 
 use std::path::Path;
-use std::rc::Rc;
+
+use clap::Parser;
+
+use cli::{Cli, Command};
+use error::{Error, ErrorKind};
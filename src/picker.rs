@@ -0,0 +1,433 @@
+/// A version offered by the picker with what we know about it.
+pub struct Choice {
+    pub version: Arc<str>,
+    pub installed: bool,
+    pub selected: bool,
+    pub size: Option<u64>,
+    pub published: Option<Arc<str>>,
+}
+
+/// Which versions the picker shows, switched with tab.
+#[derive(Clone, Copy, PartialEq)]
+enum View {
+    All,
+    Stable,
+    Installed,
+}
+
+impl View {
+    fn next(self) -> Self {
+        match self {
+            View::All => View::Stable,
+            View::Stable => View::Installed,
+            View::Installed => View::All,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            View::All => "all",
+            View::Stable => "stable",
+            View::Installed => "installed",
+        }
+    }
+}
+
+struct State<'a> {
+    choices: &'a [Choice],
+    multi: bool,
+    query: String,
+    view: View,
+    /// Minor series of the choices, newest first. `series_index` 0 shows all.
+    series: Vec<(i32, i32)>,
+    series_index: usize,
+    /// Indices into `choices` that pass the search and filters.
+    matches: Vec<usize>,
+    cursor: usize,
+    offset: usize,
+    marked: Vec<bool>,
+}
+
+impl<'a> State<'a> {
+    fn new(choices: &'a [Choice], multi: bool) -> Self {
+        let mut series = choices
+            .iter()
+            .filter_map(|choice| utils::series(&choice.version))
+            .collect::<Vec<(i32, i32)>>();
+        series.sort_by(|a, b| b.cmp(a));
+        series.dedup();
+
+        let mut state = State {
+            choices,
+            multi,
+            query: String::new(),
+            view: View::All,
+            series,
+            series_index: 0,
+            matches: Vec::new(),
+            cursor: 0,
+            offset: 0,
+            marked: vec![false; choices.len()],
+        };
+
+        state.update_matches();
+        state
+    }
+
+    /// Applies search and filters. Prefix matches come before substring
+    /// matches, which come before fuzzy matches.
+    fn update_matches(&mut self) {
+        let series = match self.series_index {
+            0 => None,
+            index => self.series.get(index - 1).copied(),
+        };
+
+        let mut scored = self
+            .choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| match self.view {
+                View::All => true,
+                View::Stable => !utils::is_prerelease(&choice.version),
+                View::Installed => choice.installed,
+            })
+            .filter(|(_, choice)| series.is_none() || utils::series(&choice.version) == series)
+            .filter_map(|(index, choice)| {
+                match_score(&self.query, &choice.version).map(|score| (score, index))
+            })
+            .collect::<Vec<(usize, usize)>>();
+
+        scored.sort();
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.cursor = 0;
+        self.offset = 0;
+    }
+
+    fn move_cursor(&mut self, rows: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        let last = self.matches.len() as isize - 1;
+        self.cursor = (self.cursor as isize + rows).clamp(0, last) as usize;
+    }
+
+    fn render(&mut self, prompt: &str, rows: usize) -> Vec<String> {
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + rows {
+            self.offset = self.cursor + 1 - rows;
+        }
+
+        let series = match self.series_index {
+            0 => "any".to_string(),
+            index => {
+                let (major, minor) = self.series[index - 1];
+                format!("{}.{}", major, minor)
+            }
+        };
+
+        let mut lines = vec![
+            format!(
+                "[{}] {} {}",
+                style("?").green().bright(),
+                prompt,
+                style(&self.query).cyan()
+            ),
+            style(format!(
+                "    view: {} | series: {} | {}",
+                self.view.name(),
+                series,
+                if self.multi {
+                    MULTI_KEYS_STR
+                } else {
+                    SINGLE_KEYS_STR
+                }
+            ))
+            .dim()
+            .to_string(),
+        ];
+
+        for (row, &index) in self.matches.iter().enumerate().skip(self.offset).take(rows) {
+            let choice = &self.choices[index];
+            let mark = match (self.multi, self.marked[index]) {
+                (false, _) => "",
+                (true, true) => "[x] ",
+                (true, false) => "[ ] ",
+            };
+            let line = format!("{}{:<24} {}", mark, choice.version, annotations(choice));
+
+            if row == self.cursor {
+                lines.push(format!(
+                    "{}{}",
+                    style("  →  ").green().bright().bold(),
+                    line
+                ));
+            } else {
+                lines.push(format!("     {}", style(line).dim()));
+            }
+        }
+
+        lines.push(
+            style(format!(
+                "    {} of {} versions",
+                self.matches.len(),
+                self.choices.len()
+            ))
+            .dim()
+            .to_string(),
+        );
+
+        lines
+    }
+
+    fn picked(&self) -> Vec<Arc<str>> {
+        let marked = self
+            .choices
+            .iter()
+            .zip(&self.marked)
+            .filter(|(_, marked)| **marked)
+            .map(|(choice, _)| choice.version.clone())
+            .collect::<Vec<Arc<str>>>();
+
+        if !marked.is_empty() {
+            return marked;
+        }
+
+        self.matches
+            .get(self.cursor)
+            .map(|&index| vec![self.choices[index].version.clone()])
+            .unwrap_or_default()
+    }
+}
+
+/// Lets people pick from `choices`. Typing searches, tab switches between
+/// all, stable and installed versions and left/right step through minor
+/// series. With `multi` space marks several versions. Callers check
+/// `cli::interactive` first.
+pub fn pick(prompt: &str, choices: &[Choice], multi: bool) -> Result<Vec<Arc<str>>, Error> {
+    let term = Term::stdout();
+    let mut state = State::new(choices, multi);
+
+    term.hide_cursor()
+        .map_err(map_error!(Io, TERMINAL_FAILED_STR))?;
+    let mut drawn = 0;
+    let result = run(&term, &mut state, prompt, &mut drawn);
+
+    if drawn > 0 {
+        let _ = term.clear_last_lines(drawn);
+    }
+    let _ = term.show_cursor();
+
+    let picked = result?;
+    if picked.is_empty() {
+        println!();
+        return Err(Error::new(ErrorKind::UserCancelled, CANCELLED_STR));
+    }
+
+    let names = picked
+        .iter()
+        .map(|version| version.as_ref())
+        .collect::<Vec<&str>>();
+    println!(
+        "[{}] {} {}",
+        style("?").green().bright(),
+        prompt,
+        style(names.join(", ")).blue()
+    );
+
+    Ok(picked)
+}
+
+/// Draws the picker until a choice is made. An empty result means the picker
+/// was closed.
+fn run(
+    term: &Term,
+    state: &mut State,
+    prompt: &str,
+    drawn: &mut usize,
+) -> Result<Vec<Arc<str>>, Error> {
+    loop {
+        let (height, width) = term.size();
+        let rows = (height as usize).saturating_sub(4).clamp(1, MAX_ROWS);
+        let lines = state.render(prompt, rows);
+
+        if *drawn > 0 {
+            term.clear_last_lines(*drawn)
+                .map_err(map_error!(Io, TERMINAL_FAILED_STR))?;
+        }
+
+        for line in &lines {
+            // Wrapped lines would throw off clearing them on the next draw.
+            term.write_line(&truncate_str(line, width as usize, ""))
+                .map_err(map_error!(Io, TERMINAL_FAILED_STR))?;
+        }
+        *drawn = lines.len();
+
+        let key = term
+            .read_key_raw()
+            .map_err(map_error!(Io, TERMINAL_FAILED_STR))?;
+
+        match key {
+            Key::ArrowUp => state.move_cursor(-1),
+            Key::ArrowDown => state.move_cursor(1),
+            Key::PageUp => state.move_cursor(-(rows as isize)),
+            Key::PageDown => state.move_cursor(rows as isize),
+            Key::Home => state.move_cursor(-(state.matches.len() as isize)),
+            Key::End => state.move_cursor(state.matches.len() as isize),
+            Key::Tab => {
+                state.view = state.view.next();
+                state.update_matches();
+            }
+            Key::ArrowRight => {
+                state.series_index = (state.series_index + 1) % (state.series.len() + 1);
+                state.update_matches();
+            }
+            Key::ArrowLeft => {
+                state.series_index = match state.series_index {
+                    0 => state.series.len(),
+                    index => index - 1,
+                };
+                state.update_matches();
+            }
+            Key::Char(' ') if state.multi => {
+                if let Some(&index) = state.matches.get(state.cursor) {
+                    state.marked[index] = !state.marked[index];
+                    state.move_cursor(1);
+                }
+            }
+            Key::Backspace => {
+                state.query.pop();
+                state.update_matches();
+            }
+            Key::Char(letter) if !letter.is_control() && letter != ' ' => {
+                state.query.push(letter);
+                state.update_matches();
+            }
+            Key::Enter if !state.picked().is_empty() => return Ok(state.picked()),
+            Key::Escape | Key::CtrlC => return Ok(Vec::new()),
+            _ => {}
+        }
+    }
+}
+
+/// Choices for `releases`, newest first. Installed versions are sized and the
+/// selected one is marked.
+pub fn choices(releases: &[ReleaseInfo], cvm_home: &Path) -> Vec<Choice> {
+    let installed = releases::installed(cvm_home).unwrap_or_default();
+    let linked = releases::linked(cvm_home).unwrap_or_default();
+    let current = releases::current_version(cvm_home).unwrap_or_else(|_| "".into());
+    let bins = cvm_home.join(crate::CVM_BINS);
+
+    let mut choices = releases
+        .iter()
+        .map(|release| {
+            let is_installed = releases::is_installed(&release.version, &installed);
+            let selected = *release.version == *current;
+
+            // Linked versions are someone else's files.
+            let size = if !is_installed || releases::is_linked(&release.version, &linked) {
+                None
+            } else {
+                Some(utils::dir_size(
+                    &bins.join(format!("cmake-{}", release.version)),
+                ))
+            };
+
+            Choice {
+                version: release.version.clone(),
+                installed: is_installed,
+                selected,
+                size,
+                published: release.published.clone(),
+            }
+        })
+        .collect::<Vec<Choice>>();
+
+    choices.sort_by(|a, b| utils::compare_versions(&b.version, &a.version));
+    choices
+}
+
+/// Choices for installed versions with publish dates from the release cache.
+pub fn installed_choices(installed: &[Arc<str>], cvm_home: &Path) -> Vec<Choice> {
+    let cached = releases::cached_release_info(cvm_home).unwrap_or_default();
+
+    let releases = installed
+        .iter()
+        .map(|version| {
+            cached
+                .iter()
+                .find(|release| release.version == *version)
+                .map(|release| ReleaseInfo {
+                    version: version.clone(),
+                    published: release.published.clone(),
+                })
+                .unwrap_or_else(|| ReleaseInfo::new(version.clone()))
+        })
+        .collect::<Vec<ReleaseInfo>>();
+
+    choices(&releases, cvm_home)
+}
+
+fn annotations(choice: &Choice) -> String {
+    let mut notes = Vec::new();
+
+    if choice.selected {
+        notes.push("selected".to_string());
+    } else if choice.installed {
+        notes.push("installed".to_string());
+    }
+
+    if let Some(size) = choice.size.filter(|size| *size > 0) {
+        if !choice.installed {
+            notes.push("cached".to_string());
+        }
+
+        notes.push(utils::format_size(size));
+    }
+
+    if let Some(published) = &choice.published {
+        notes.push(published.chars().take(10).collect());
+    }
+
+    notes.join("  ")
+}
+
+/// Ranks `version` for `query`: 0 for a prefix, 1 for a substring and 2 when
+/// the query letters appear in order. `None` when it does not match.
+fn match_score(query: &str, version: &str) -> Option<usize> {
+    if version.starts_with(query) {
+        return Some(0);
+    }
+
+    if version.contains(query) {
+        return Some(1);
+    }
+
+    let mut letters = version.chars();
+    if query
+        .chars()
+        .all(|wanted| letters.any(|letter| letter == wanted))
+    {
+        return Some(2);
+    }
+
+    None
+}
+
+const MAX_ROWS: usize = 12;
+const SINGLE_KEYS_STR: &str = "type to search, tab: view, ←/→: series, enter: pick, esc: cancel";
+const MULTI_KEYS_STR: &str =
+    "type to search, tab: view, ←/→: series, space: mark, enter: pick, esc: cancel";
+const TERMINAL_FAILED_STR: &str = "Failed to use the terminal.";
+const CANCELLED_STR: &str = "Session was canceled.";
+
+use std::path::Path;
+use std::sync::Arc;
+
+use console::{style, truncate_str, Key, Term};
+
+use crate::error::{Error, ErrorKind};
+use crate::macros::map_error;
+use crate::releases::{self, ReleaseInfo};
+use crate::utils;
@@ -0,0 +1,131 @@
+/// A version a project asks for in a .cmake-version file.
+pub struct Pin {
+    pub version: Arc<str>,
+    pub file: PathBuf,
+}
+
+/// Looks for a .cmake-version file in the current directory and its parents.
+/// The nearest one wins.
+pub fn find() -> Result<Option<Pin>, Error> {
+    let current_dir =
+        std::env::current_dir().map_err(map_error!(Io, "Failed to read current directory."))?;
+
+    for dir in current_dir.ancestors() {
+        let file = dir.join(PIN_FILE);
+        if !file.is_file() {
+            continue;
+        }
+
+        return read(file).map(Some);
+    }
+
+    Ok(None)
+}
+
+/// Finds the .cmake-version files in `dir` and the directories below it.
+/// Hidden directories and symlinks are skipped.
+pub fn scan(dir: &Path) -> Result<Vec<Pin>, Error> {
+    let mut pins = Vec::new();
+    let mut pending = vec![(dir.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = std::fs::read_dir(&dir).map_err(|error| {
+            let message = format!("Failed to read '{}'.", dir.display());
+            Error::new(ErrorKind::Io, message).caused_by(error)
+        })?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+
+            if file_type.is_file() && entry.file_name() == PIN_FILE {
+                match read(path) {
+                    Ok(pin) => pins.push(pin),
+                    Err(error) => log::warning(&error.to_string()),
+                }
+            } else if file_type.is_dir()
+                && depth < SCAN_DEPTH
+                && !entry.file_name().to_string_lossy().starts_with('.')
+            {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+
+    Ok(pins)
+}
+
+fn read(file: PathBuf) -> Result<Pin, Error> {
+    let contents = std::fs::read_to_string(&file).map_err(|error| {
+        let message = format!("Failed to read '{}'.", file.display());
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })?;
+
+    let version = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::Usage,
+                format!("'{}' does not name a version.", file.display()),
+            )
+        })?;
+
+    Ok(Pin {
+        version: version.into(),
+        file,
+    })
+}
+
+/// The version to use when cvm may not prompt for one.
+pub fn required() -> Result<Arc<str>, Error> {
+    match find()? {
+        Some(pin) => {
+            status!("Using v{} from '{}'.", pin.version, pin.file.display());
+            Ok(pin.version)
+        }
+        None => Err(cli::no_input_error(NO_PIN_HINT_STR)),
+    }
+}
+
+/// Points `pin` at `version`. Comments and other lines in the file are kept.
+pub fn update(pin: &Pin, version: &str) -> Result<(), Error> {
+    let contents = std::fs::read_to_string(&pin.file).map_err(|error| {
+        let message = format!("Failed to read '{}'.", pin.file.display());
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })?;
+
+    let mut replaced = false;
+    let mut updated = String::new();
+    for line in contents.lines() {
+        if !replaced && line.trim() == pin.version.as_ref() {
+            updated.push_str(version);
+            replaced = true;
+        } else {
+            updated.push_str(line);
+        }
+
+        updated.push('\n');
+    }
+
+    std::fs::write(&pin.file, updated).map_err(|error| {
+        let message = format!("Failed to write '{}'.", pin.file.display());
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })
+}
+
+const PIN_FILE: &str = ".cmake-version";
+const SCAN_DEPTH: usize = 6;
+const NO_PIN_HINT_STR: &str = "Pass a version or add a .cmake-version file to the project.";
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::cli;
+use crate::error::{Error, ErrorKind};
+use crate::log;
+use crate::macros::{map_error, status};
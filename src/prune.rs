@@ -0,0 +1,324 @@
+/// Which installed versions a prune keeps.
+struct Policy {
+    /// Only looks at nightlies and keeps the newest ones.
+    nightlies: Option<usize>,
+    /// Keeps the most recently selected versions.
+    keep_recent: Option<usize>,
+    /// Keeps the newest patch release of every minor series.
+    keep_latest: bool,
+    /// Directories searched for .cmake-version files.
+    scan: Vec<PathBuf>,
+}
+
+/// What a prune does with an installed version.
+struct Decision {
+    version: Arc<str>,
+    /// Why the version is kept, `None` when it is removed.
+    kept_by: Option<String>,
+    size: u64,
+}
+
+pub fn prune(args: &PruneArgs, cvm_home: &Path) -> Result<(), Error> {
+    if !args.nightlies && args.keep_recent.is_none() && !args.keep_latest {
+        return Err(Error::new(ErrorKind::Usage, NO_POLICY_STR));
+    }
+
+    let policy = Policy {
+        nightlies: args.nightlies.then_some(args.keep),
+        keep_recent: args.keep_recent,
+        keep_latest: args.keep_latest,
+        scan: args.scan.clone(),
+    };
+
+    // Held from planning to the last removal, so nothing is installed or
+    // selected in between.
+    let _lock = if args.dry_run {
+        None
+    } else {
+        Some(lock::acquire(cvm_home)?)
+    };
+
+    let decisions = plan(&policy, cvm_home)?;
+    let (kept, pruned): (Vec<&Decision>, Vec<&Decision>) = decisions
+        .iter()
+        .partition(|decision| decision.kept_by.is_some());
+    let reclaimed = pruned.iter().map(|decision| decision.size).sum::<u64>();
+
+    if !args.dry_run {
+        for decision in &pruned {
+            status!("Pruning v{}...", decision.version);
+            remove_installed(cvm_home, &decision.version)?;
+        }
+    }
+
+    if cli::json() {
+        let message = if args.dry_run {
+            "would be pruned"
+        } else {
+            "pruned"
+        };
+        let outcomes = pruned
+            .iter()
+            .map(|decision| Outcome::new(&decision.version, Status::Pruned, message))
+            .collect::<Vec<Outcome>>();
+
+        report::print_json(&json!({
+            "results": outcomes,
+            "reclaimed": reclaimed,
+            "dry_run": args.dry_run,
+        }));
+        return Ok(());
+    }
+
+    if pruned.is_empty() {
+        println!(
+            "{}",
+            if args.nightlies {
+                NO_NIGHTLIES_STR
+            } else {
+                NOTHING_STR
+            }
+        );
+        return Ok(());
+    }
+
+    if !kept.is_empty() {
+        println!("Keeping:");
+        for decision in &kept {
+            let kept_by = decision.kept_by.as_deref().unwrap_or("");
+            println!("    {:<24} {}", decision.version, kept_by);
+        }
+    }
+
+    println!(
+        "{}",
+        if args.dry_run {
+            "Would remove:"
+        } else {
+            "Removed:"
+        }
+    );
+    for decision in &pruned {
+        println!(
+            "    {:<24} {}",
+            decision.version,
+            utils::format_size(decision.size)
+        );
+    }
+
+    if args.dry_run {
+        println!("Would reclaim {}.", utils::format_size(reclaimed));
+    } else {
+        println!("Reclaimed {}.", utils::format_size(reclaimed));
+    }
+
+    Ok(())
+}
+
+/// Decides for every installed version whether a prune keeps it. The selected
+/// version, protected versions and pinned versions are always kept. Linked
+/// versions are someone else's files and are left alone.
+fn plan(policy: &Policy, cvm_home: &Path) -> Result<Vec<Decision>, Error> {
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+    let linked = linked(cvm_home)?;
+    let protected = protected(cvm_home)?;
+
+    // A broken pin names no version, so it has nothing to keep.
+    let mut pins = match pin::find() {
+        Ok(pin) => pin.into_iter().collect::<Vec<Pin>>(),
+        Err(error) => {
+            log::warning(&error.to_string());
+            Vec::new()
+        }
+    };
+    for dir in &policy.scan {
+        pins.extend(pin::scan(dir)?);
+    }
+
+    let mut candidates = installed(cvm_home)?
+        .into_iter()
+        .filter(|version| !is_linked(version, &linked))
+        .filter(|version| policy.nightlies.is_none() || utils::is_nightly(version))
+        .collect::<Vec<Arc<str>>>();
+
+    // Newest first, so taking from the front keeps the newest.
+    candidates.sort_by(|a, b| utils::compare_versions(b, a));
+
+    let newest_nightlies = candidates
+        .iter()
+        .filter(|version| utils::is_nightly(version))
+        .take(policy.nightlies.unwrap_or(0))
+        .cloned()
+        .collect::<Vec<Arc<str>>>();
+
+    let mut recent = candidates.clone();
+    recent.sort_by_key(|version| Reverse(last_used(cvm_home, version).unwrap_or(0)));
+    recent.truncate(policy.keep_recent.unwrap_or(0));
+
+    let mut newest_patches: Vec<Arc<str>> = Vec::new();
+    if policy.keep_latest {
+        for version in &candidates {
+            let is_newest = utils::is_version_number(version)
+                && !newest_patches
+                    .iter()
+                    .any(|newest| utils::series(newest) == utils::series(version));
+
+            if is_newest {
+                newest_patches.push(version.clone());
+            }
+        }
+    }
+
+    let bins = cvm_home.join(crate::CVM_BINS);
+    let decisions = candidates
+        .into_iter()
+        .map(|version| {
+            let kept_by = if version == current {
+                Some("selected".to_string())
+            } else if protected.contains(&version) {
+                Some("protected".to_string())
+            } else if let Some(pin) = pins.iter().find(|pin| pin.version == version) {
+                Some(format!("pinned by '{}'", pin.file.display()))
+            } else if recent.contains(&version) {
+                Some("recently used".to_string())
+            } else if newest_patches.contains(&version) {
+                Some("newest patch of its series".to_string())
+            } else if newest_nightlies.contains(&version) {
+                Some("newest nightly".to_string())
+            } else {
+                None
+            };
+
+            let size = match kept_by {
+                Some(_) => 0,
+                None => utils::dir_size(&bins.join(format!("cmake-{}", version))),
+            };
+
+            Decision {
+                version,
+                kept_by,
+                size,
+            }
+        })
+        .collect();
+
+    Ok(decisions)
+}
+
+/// Removes installed nightlies beyond the newest `keep` and returns them. The
+/// selected, protected and pinned versions are never removed. Callers hold
+/// the state lock.
+pub fn prune_nightlies(cvm_home: &Path, keep: usize) -> Result<Vec<Arc<str>>, Error> {
+    let policy = Policy {
+        nightlies: Some(keep),
+        keep_recent: None,
+        keep_latest: false,
+        scan: Vec::new(),
+    };
+
+    let stale = plan(&policy, cvm_home)?
+        .into_iter()
+        .filter(|decision| decision.kept_by.is_none())
+        .map(|decision| decision.version)
+        .collect::<Vec<Arc<str>>>();
+
+    for version in &stale {
+        status!("Pruning nightly {}...", version);
+        remove_installed(cvm_home, version)?;
+    }
+
+    Ok(stale)
+}
+
+/// Prunes nightlies after an install when `nightly_keep` is set in the cvm
+/// config.
+pub fn auto_prune(cvm_home: &Path) -> Result<(), Error> {
+    let keep = match config::value(cvm_home, "nightly_keep")? {
+        Some(keep) => keep,
+        None => return Ok(()),
+    };
+
+    let keep = keep
+        .parse::<usize>()
+        .map_err(map_error!(Usage, "Config 'nightly_keep' must be a number."))?;
+
+    prune_nightlies(cvm_home, keep)?;
+    Ok(())
+}
+
+/// Protects an installed version from `cvm prune`, or lists the protected
+/// versions.
+pub fn protect(args: &ProtectArgs, cvm_home: &Path) -> Result<(), Error> {
+    let version = match &args.version {
+        Some(version) => version.as_str(),
+        None => {
+            let protected = protected(cvm_home)?;
+
+            if cli::json() {
+                let protected = protected
+                    .iter()
+                    .map(|version| version.as_ref())
+                    .collect::<Vec<&str>>();
+                report::print_json(&json!({ "protected": protected }));
+            } else if protected.is_empty() {
+                println!("No versions are protected.");
+            } else {
+                for version in &protected {
+                    println!("    {}", version);
+                }
+            }
+
+            return Ok(());
+        }
+    };
+
+    let _lock = lock::acquire(cvm_home)?;
+    if args.remove {
+        unset_protected(cvm_home, version)?;
+    } else {
+        if !is_installed(version, &installed(cvm_home)?) {
+            return Err(Error::new(
+                ErrorKind::NotInstalled,
+                format!("CMake v{} is not installed.", version),
+            ));
+        }
+
+        set_protected(cvm_home, version)?;
+    }
+
+    if cli::json() {
+        report::print_json(&json!({ "version": version, "protected": !args.remove }));
+    } else if args.remove {
+        println!("CMake v{} is no longer protected.", version);
+    } else {
+        println!("CMake v{} is protected from 'cvm prune'.", version);
+    }
+
+    Ok(())
+}
+
+const NO_POLICY_STR: &str =
+    "Pass --nightlies, --keep-recent or --keep-latest to choose what to keep.";
+const NO_NIGHTLIES_STR: &str = "No nightlies to prune.";
+const NOTHING_STR: &str = "Nothing to prune.";
+
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::cli::{self, ProtectArgs, PruneArgs};
+use crate::config;
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::log;
+use crate::macros::{map_error, status};
+use crate::pin::{self, Pin};
+use crate::releases::{
+    current_version, installed, is_installed, is_linked, last_used, linked, protected,
+    set_protected, unset_protected,
+};
+use crate::remove::remove_installed;
+use crate::report::{self, Outcome, Status};
+use crate::utils;
@@ -1,80 +1,446 @@
 #[derive(Deserialize)]
 struct Release {
-    tag_name: Rc<str>,
+    tag_name: Arc<str>,
+    published_at: Option<Arc<str>>,
+    #[serde(default)]
+    prerelease: bool,
+    body: Option<Arc<str>>,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
 }
 
-pub fn releases() -> Result<Vec<Rc<str>>, Rc<str>> {
+/// A file attached to a github release.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ReleaseAsset {
+    pub name: Arc<str>,
+    pub size: u64,
+    #[serde(rename(deserialize = "browser_download_url"))]
+    pub url: Arc<str>,
+}
+
+/// Everything github tells about a single release.
+pub struct ReleaseDetails {
+    pub published: Option<Arc<str>>,
+    pub prerelease: bool,
+    pub notes: Option<Arc<str>>,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// Where and when a version was installed, see `set_origin`.
+pub struct Origin {
+    /// Seconds since the unix epoch.
+    pub installed_at: u64,
+    pub source: Arc<str>,
+}
+
+/// A release as listed by a source. Only github knows when a release was
+/// published.
+#[derive(Clone)]
+pub struct ReleaseInfo {
+    pub version: Arc<str>,
+    pub published: Option<Arc<str>>,
+}
+
+impl ReleaseInfo {
+    pub fn new(version: Arc<str>) -> Self {
+        ReleaseInfo {
+            version,
+            published: None,
+        }
+    }
+}
+
+pub fn releases() -> Result<Vec<ReleaseInfo>, Error> {
+    cli::ensure_online(GITHUB_RELEASES_URL)?;
+
     let response = blocking::Client::new()
-        .get("https://api.github.com/repos/Kitware/CMake/releases?per_page=100")
+        .get(format!("{}?per_page=100", GITHUB_RELEASES_URL))
         .header(
             header::USER_AGENT,
             format!("cvm {} request", env!("CARGO_PKG_VERSION")),
         )
         .header(header::ACCEPT, "application/vnd.github.v3+json")
         .send()
-        .map_err(map_error!("Failed to request releases from github. ({})"))?;
+        .map_err(map_error!(
+            Network,
+            "Failed to request releases from github."
+        ))?;
+
+    if !response.status().is_success() {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("GitHub responded with {}.", response.status()),
+        ));
+    }
 
     let releases = response
         .json::<Vec<Release>>()
-        .map_err(map_error!("Failed to parse releases from response. ({})"))?
-        .iter()
-        .map(|release| release.tag_name[1..].into()) // remove prefix 'v' from the version
+        .map_err(map_error!(
+            Network,
+            "Failed to parse releases from response."
+        ))?
+        .into_iter()
+        .map(|release| ReleaseInfo {
+            version: release.tag_name[1..].into(), // remove prefix 'v' from the version
+            published: release.published_at,
+        })
         .collect();
 
     Ok(releases)
 }
 
-pub fn cached_releases(cvm_home: &Path) -> Result<Vec<Rc<str>>, Rc<str>> {
+/// Fetches the github release of `version`.
+pub fn release_details(version: &str) -> Result<ReleaseDetails, Error> {
+    let url = format!("{}/tags/v{}", GITHUB_RELEASES_URL, version);
+    cli::ensure_online(&url)?;
+
+    let response = blocking::Client::new()
+        .get(&url)
+        .header(
+            header::USER_AGENT,
+            format!("cvm {} request", env!("CARGO_PKG_VERSION")),
+        )
+        .header(header::ACCEPT, "application/vnd.github.v3+json")
+        .send()
+        .map_err(map_error!(
+            Network,
+            "Failed to request the release from github."
+        ))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("GitHub has no release v{}.", version),
+        ));
+    }
+
+    if !response.status().is_success() {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("GitHub responded with {}.", response.status()),
+        ));
+    }
+
+    let release = response.json::<Release>().map_err(map_error!(
+        Network,
+        "Failed to parse the release from response."
+    ))?;
+
+    Ok(ReleaseDetails {
+        published: release.published_at,
+        prerelease: release.prerelease,
+        notes: release.body,
+        assets: release.assets,
+    })
+}
+
+pub fn cached_releases(cvm_home: &Path) -> Result<Vec<Arc<str>>, Error> {
+    Ok(cached_release_info(cvm_home)?
+        .into_iter()
+        .map(|release| release.version)
+        .collect())
+}
+
+/// Reads the release cache. Each line holds a version, optionally followed by
+/// a tab and the date it was published.
+pub fn cached_release_info(cvm_home: &Path) -> Result<Vec<ReleaseInfo>, Error> {
     let file_path = cvm_home.join(crate::CVM_CACHE);
     let file = std::fs::File::open(file_path)
-        .map_err(map_error!("Failed to get cached releases. ({})"))?;
+        .map_err(map_error!(StateCorrupt, "Failed to get cached releases."))?;
     let buf = std::io::BufReader::new(file).lines();
 
     let mut cached_versions = Vec::new();
-    for line in buf {
-        if let Ok(version) = line {
-            cached_versions.push(Rc::from(version));
-        }
+    for line in buf.map_while(Result::ok) {
+        let release = match line.split_once('\t') {
+            Some((version, published)) => ReleaseInfo {
+                version: version.into(),
+                published: Some(published.into()),
+            },
+            None => ReleaseInfo::new(line.into()),
+        };
+
+        cached_versions.push(release);
     }
 
     Ok(cached_versions)
 }
 
-pub fn installed(cvm_home: &Path) -> Result<Vec<Rc<str>>, Rc<str>> {
-    const ERR_STR: &'static str = "Failed to get installed cmake versions.";
+/// Replaces the release cache with `releases`, newest first.
+pub fn set_cached_releases(cvm_home: &Path, releases: &[ReleaseInfo]) -> Result<(), Error> {
+    let data = releases
+        .iter()
+        .map(|release| match &release.published {
+            Some(published) => format!("{}\t{}\n", release.version, published),
+            None => format!("{}\n", release.version),
+        })
+        .collect::<String>();
+
+    std::fs::write(cvm_home.join(crate::CVM_CACHE), data)
+        .map_err(map_error!(Io, "Failed to write release to cache file."))
+}
+
+pub fn installed(cvm_home: &Path) -> Result<Vec<Arc<str>>, Error> {
     let file_path = cvm_home.join(crate::CVM_INSTALLED);
-    let file = std::fs::File::open(file_path)
-        .map_err(|error| Rc::from(format!("{} ({})", ERR_STR, error)))?;
+    let file = std::fs::File::open(file_path).map_err(map_error!(
+        StateCorrupt,
+        "Failed to get installed cmake versions."
+    ))?;
 
     let buf = std::io::BufReader::new(file).lines();
     let mut installed_versions = Vec::new();
 
-    for line in buf {
-        if let Ok(version) = line {
-            installed_versions.push(Rc::from(version));
-        }
+    for version in buf.map_while(Result::ok) {
+        installed_versions.push(Arc::from(version));
     }
 
     Ok(installed_versions)
 }
 
-pub fn current_version(cvm_home: &Path) -> Result<Rc<str>, Rc<str>> {
-    const ERR_STR: &'static str = "Failed to get currently installed version.";
+/// Returns the names and paths of versions registered with 'cvm link'. These
+/// live outside of the bins directory and must never be deleted by cvm.
+pub fn linked(cvm_home: &Path) -> Result<Vec<(Arc<str>, PathBuf)>, Error> {
+    let file_path = cvm_home.join(crate::CVM_LINKED);
+
+    // Homes created before linking existed do not have the file yet.
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = std::fs::File::open(file_path).map_err(map_error!(
+        StateCorrupt,
+        "Failed to get linked cmake versions."
+    ))?;
+
+    let buf = std::io::BufReader::new(file).lines();
+    let mut linked_versions = Vec::new();
+
+    for line in buf.map_while(Result::ok) {
+        if let Some((name, path)) = line.split_once('\t') {
+            linked_versions.push((Arc::from(name), PathBuf::from(path)));
+        }
+    }
+
+    Ok(linked_versions)
+}
+
+pub fn is_linked(name: &str, linked: &[(Arc<str>, PathBuf)]) -> bool {
+    linked
+        .iter()
+        .any(|(linked_name, _)| linked_name.as_ref() == name)
+}
+
+pub fn set_linked(cvm_home: &Path, name: &str, path: &Path) -> Result<(), Error> {
+    let file_path = cvm_home.join(crate::CVM_LINKED);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)
+        .map_err(map_error!(Io, "Failed to open cvm_linked file."))?;
+
+    let data = format!("{}\t{}\n", name, path.to_str().unwrap_or(""));
+    file.write(data.as_bytes())
+        .map_err(map_error!(Io, "Failed to write linked version to file."))?;
+
+    Ok(())
+}
+
+pub fn unset_linked(cvm_home: &Path, name: &str) -> Result<(), Error> {
+    let remaining = linked(cvm_home)?
+        .into_iter()
+        .filter(|(linked_name, _)| linked_name.as_ref() != name)
+        .map(|(linked_name, path)| format!("{}\t{}\n", linked_name, path.to_str().unwrap_or("")))
+        .collect::<String>();
+
+    std::fs::write(cvm_home.join(crate::CVM_LINKED), remaining)
+        .map_err(map_error!(Io, "Failed to update cvm_linked file."))
+}
+
+/// Returns the directory an installed version lives in, `bins/cmake-<version>`
+/// whether or not it is selected. Linked versions resolve to the linked
+/// directory.
+pub fn install_prefix(cvm_home: &Path, version: &str) -> Result<PathBuf, Error> {
+    if !is_installed(version, &installed(cvm_home)?) {
+        return Err(Error::new(
+            ErrorKind::NotInstalled,
+            format!(
+                "CMake version {0} is not installed. Please run 'cvm install {0}'",
+                version
+            ),
+        ));
+    }
+
+    if let Some((_, path)) = linked(cvm_home)?
+        .into_iter()
+        .find(|(name, _)| name.as_ref() == version)
+    {
+        return Ok(path);
+    }
+
+    let prefix = cvm_home
+        .join(crate::CVM_BINS)
+        .join(format!("cmake-{}", version));
+
+    std::path::absolute(&prefix).map_err(map_error!(Io, "Failed to resolve install path."))
+}
+
+/// Returns where and when `version` was installed. Versions installed before
+/// cvm kept track of this have no origin.
+pub fn origin(cvm_home: &Path, version: &str) -> Option<Origin> {
+    let contents = std::fs::read_to_string(cvm_home.join(crate::CVM_ORIGINS)).ok()?;
+
+    contents.lines().find_map(|line| {
+        let mut fields = line.splitn(3, '\t');
+        if fields.next()? != version {
+            return None;
+        }
+
+        Some(Origin {
+            installed_at: fields.next()?.parse().ok()?,
+            source: fields.next()?.into(),
+        })
+    })
+}
+
+/// Remembers that `version` was just installed from `source`, example
+/// `github`, `cache` or `adopt:/opt/cmake`.
+pub fn set_origin(cvm_home: &Path, version: &str, source: &str) -> Result<(), Error> {
+    let installed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    unset_origin(cvm_home, version)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(cvm_home.join(crate::CVM_ORIGINS))
+        .map_err(map_error!(Io, "Failed to open cvm_origins file."))?;
+
+    let data = format!("{}\t{}\t{}\n", version, installed_at, source);
+    file.write_all(data.as_bytes())
+        .map_err(map_error!(Io, "Failed to write origin to file."))
+}
+
+pub fn unset_origin(cvm_home: &Path, version: &str) -> Result<(), Error> {
+    remove_entry(&cvm_home.join(crate::CVM_ORIGINS), version)
+}
+
+/// Returns when `version` was last selected, or installed when it never was.
+pub fn last_used(cvm_home: &Path, version: &str) -> Option<u64> {
+    let used = std::fs::read_to_string(cvm_home.join(crate::CVM_USED))
+        .ok()
+        .and_then(|contents| {
+            contents.lines().find_map(|line| {
+                let (name, used_at) = line.split_once('\t')?;
+                (name == version).then(|| used_at.parse().ok())?
+            })
+        });
+
+    used.or_else(|| origin(cvm_home, version).map(|origin| origin.installed_at))
+}
+
+fn set_last_used(cvm_home: &Path, version: &str) -> Result<(), Error> {
+    let used_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    unset_last_used(cvm_home, version)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(cvm_home.join(crate::CVM_USED))
+        .map_err(map_error!(Io, "Failed to open cvm_used file."))?;
+
+    let data = format!("{}\t{}\n", version, used_at);
+    file.write_all(data.as_bytes())
+        .map_err(map_error!(Io, "Failed to write last use to file."))
+}
+
+pub fn unset_last_used(cvm_home: &Path, version: &str) -> Result<(), Error> {
+    remove_entry(&cvm_home.join(crate::CVM_USED), version)
+}
+
+/// Versions `cvm prune` never removes.
+pub fn protected(cvm_home: &Path) -> Result<Vec<Arc<str>>, Error> {
+    let file_path = cvm_home.join(crate::CVM_PROTECTED);
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = std::fs::read_to_string(file_path).map_err(map_error!(
+        StateCorrupt,
+        "Failed to read cvm_protected file."
+    ))?;
+
+    Ok(contents
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(Arc::from)
+        .collect())
+}
+
+pub fn set_protected(cvm_home: &Path, version: &str) -> Result<(), Error> {
+    unset_protected(cvm_home, version)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(cvm_home.join(crate::CVM_PROTECTED))
+        .map_err(map_error!(Io, "Failed to open cvm_protected file."))?;
+
+    file.write_all(format!("{}\n", version).as_bytes())
+        .map_err(map_error!(Io, "Failed to write protected version to file."))
+}
+
+pub fn unset_protected(cvm_home: &Path, version: &str) -> Result<(), Error> {
+    remove_entry(&cvm_home.join(crate::CVM_PROTECTED), version)
+}
+
+/// Drops the lines of a tracking file that start with `version`.
+fn remove_entry(file_path: &Path, version: &str) -> Result<(), Error> {
+    if !file_path.exists() {
+        return Ok(());
+    }
+
+    let contents = std::fs::read_to_string(file_path).map_err(|error| {
+        let message = format!("Failed to read '{}'.", file_path.display());
+        Error::new(ErrorKind::StateCorrupt, message).caused_by(error)
+    })?;
+
+    let remaining = contents
+        .lines()
+        .filter(|line| line.split('\t').next() != Some(version))
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+
+    std::fs::write(file_path, remaining).map_err(|error| {
+        let message = format!("Failed to update '{}'.", file_path.display());
+        Error::new(ErrorKind::Io, message).caused_by(error)
+    })
+}
+
+pub fn current_version(cvm_home: &Path) -> Result<Arc<str>, Error> {
+    const ERR_STR: &str = "Failed to get currently installed version.";
     let file_path = cvm_home.join(crate::CVM_CURRENT_FILE);
-    let file = std::fs::File::open(file_path)
-        .map_err(|error| Rc::from(format!("{} ({})", ERR_STR, error)))?;
+    let file = std::fs::File::open(file_path).map_err(map_error!(StateCorrupt, ERR_STR))?;
     let mut lines = std::io::BufReader::new(file).lines();
 
     match lines.next() {
         Some(current_version) => match current_version {
             Ok(version) => Ok(version.into()),
-            Err(error) => Err(format!("{} ({})", ERR_STR, error).into()),
+            Err(error) => Err(Error::new(ErrorKind::StateCorrupt, ERR_STR).caused_by(error)),
         },
-        None => Err(format!("{} (No version of cmake has been installed)", ERR_STR).into()),
+        None => Err(Error::new(
+            ErrorKind::NotInstalled,
+            format!("{} (No version of cmake has been installed)", ERR_STR),
+        )),
     }
 }
 
-pub fn is_installed(in_version: &str, installed: &Vec<Rc<str>>) -> bool {
+pub fn is_installed(in_version: &str, installed: &[Arc<str>]) -> bool {
     for version in installed {
         if version.as_ref() == in_version {
             return true;
@@ -84,64 +450,103 @@ pub fn is_installed(in_version: &str, installed: &Vec<Rc<str>>) -> bool {
     false
 }
 
-pub fn set_installed(cvm_home: &Path, install: &str) -> Result<(), Rc<str>> {
+pub fn set_installed(cvm_home: &Path, install: &str) -> Result<(), Error> {
     let file_path = cvm_home.join(crate::CVM_INSTALLED);
     let mut file = std::fs::OpenOptions::new()
-        .write(true)
         .append(true)
         .open(file_path)
-        .map_err(map_error!("Failed to open cvm_current file. ({})"))?;
+        .map_err(map_error!(Io, "Failed to open cvm_current file."))?;
 
     let data = format!("{}\n", install);
     file.write(data.as_bytes())
-        .map_err(map_error!("Failed to write current install to file. ({})"))?;
+        .map_err(map_error!(Io, "Failed to write current install to file."))?;
 
     Ok(())
 }
 
-pub fn set_current_install(cvm_home: &Path, install: &str) -> Result<(), Rc<str>> {
+/// Replaces the installed list. The list is written next to cvm_installed and
+/// renamed over it, so it is never left partly written.
+pub fn write_installed(cvm_home: &Path, installed: &[Arc<str>]) -> Result<(), Error> {
+    let file_path = cvm_home.join(crate::CVM_INSTALLED);
+    let temp_path = cvm_home.join(format!("{}.tmp", crate::CVM_INSTALLED));
+
+    let data = installed
+        .iter()
+        .map(|version| format!("{}\n", version))
+        .collect::<String>();
+
+    std::fs::write(&temp_path, data)
+        .map_err(map_error!(Io, "Failed to write cvm_installed file."))?;
+    std::fs::rename(&temp_path, &file_path)
+        .map_err(map_error!(Io, "Failed to replace cvm_installed file."))
+}
+
+pub fn set_current_install(cvm_home: &Path, install: &str) -> Result<(), Error> {
     let file_path = cvm_home.join(crate::CVM_CURRENT_FILE);
     let mut file = std::fs::OpenOptions::new()
         .write(true)
         .open(file_path)
-        .map_err(map_error!("Failed to open cvm_current file. ({})"))?;
+        .map_err(map_error!(Io, "Failed to open cvm_current file."))?;
 
     file.set_len(0)
-        .map_err(map_error!("Failed to clear cvm_current file. ({})"))?;
+        .map_err(map_error!(Io, "Failed to clear cvm_current file."))?;
 
     file.seek(std::io::SeekFrom::Start(0))
-        .map_err(map_error!("Failed to seek to beginning of file. ({})"))?;
+        .map_err(map_error!(Io, "Failed to seek to beginning of file."))?;
 
     file.write(install.as_bytes())
-        .map_err(map_error!("Failed to write current install to file. ({})"))?;
+        .map_err(map_error!(Io, "Failed to write current install to file."))?;
 
-    Ok(())
+    // An empty install clears the selection.
+    if install.is_empty() {
+        return Ok(());
+    }
+
+    set_last_used(cvm_home, install)
 }
 
-pub fn latest_release() -> Result<Rc<str>, Rc<str>> {
+pub fn latest_release() -> Result<Arc<str>, Error> {
+    cli::ensure_online(GITHUB_RELEASES_URL)?;
+
     let response = blocking::Client::new()
-        .get("https://api.github.com/repos/Kitware/CMake/releases/latest")
+        .get(format!("{}/latest", GITHUB_RELEASES_URL))
         .header(
             header::USER_AGENT,
             format!("cvm {} request", env!("CARGO_PKG_VERSION")),
         )
         .header(header::ACCEPT, "application/vnd.github.v3+json")
         .send()
-        .map_err(map_error!("Failed to request releases from github. ({})"))?;
+        .map_err(map_error!(
+            Network,
+            "Failed to request releases from github."
+        ))?;
+
+    if !response.status().is_success() {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("GitHub responded with {}.", response.status()),
+        ));
+    }
 
-    let release = response
-        .json::<Release>()
-        .map_err(map_error!("Failed to parse releases from response. ({})"))?;
+    let release = response.json::<Release>().map_err(map_error!(
+        Network,
+        "Failed to parse releases from response."
+    ))?;
 
     Ok(release.tag_name[1..].into())
 }
 
+const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/Kitware/CMake/releases";
+
 use std::io::Write;
 use std::io::{BufRead, Seek};
-use std::path::Path;
-use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use reqwest::{blocking, header};
-use serde::Deserialize;
+use reqwest::{blocking, header, StatusCode};
+use serde::{Deserialize, Serialize};
 
+use crate::cli;
+use crate::error::{Error, ErrorKind};
 use crate::macros::map_error;
@@ -1,124 +1,477 @@
-pub fn remove(args: &Vec<Rc<str>>, cvm_home: &Path) -> Result<(), Rc<str>> {
-    let mut installed = installed(cvm_home)?;
+pub fn remove(args: &RemoveArgs, cvm_home: &Path) -> Result<(), Error> {
+    if args.all {
+        return remove_all(args, cvm_home);
+    }
+
+    // The picker may wait on the user, so the lock is only taken after it
+    // and the installed versions are read again under it.
+    let tags = get_tags(&args.versions, &installed(cvm_home)?, cvm_home)?;
+
+    let _lock = lock::acquire(cvm_home)?;
+    let installed = installed(cvm_home)?;
+    let mut outcomes = Vec::new();
 
-    let tag = get_tag(args, &installed)?;
+    // We don't mind if no version is selected.
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
 
-    if tag.is_empty() {
-        return Ok(());
+    status!("Checking if the version we are removing is in use...");
+    let removes_current = tags.contains(&current);
+    if removes_current && !args.force {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!(
+                "Cannot remove v{} since it is the selected version.\nSwitch before removing, pass --force, or use --all to remove everything.",
+                current
+            ),
+        ));
     }
 
-    if tag.as_ref() == "--all" {
-        println!(
-            "Removing all CMake version and contents in '{}'.",
-            cvm_home.to_str().unwrap_or("")
-        );
+    // The selected version goes last so the fallback is picked from what
+    // remains. A version that fails does not stop the others.
+    for tag in tags.iter().filter(|tag| **tag != current) {
+        outcomes.push(outcome(
+            tag,
+            tags.len(),
+            remove_version(tag, &installed, cvm_home),
+        ));
+    }
 
-        std::fs::remove_dir_all(cvm_home)
-            .map_err(map_error!("Failed to remove $HOME/.cvm directory. ({})"))?;
+    let mut fallback = Ok(());
+    if removes_current {
+        let removed = deselect(&current, cvm_home)
+            .and_then(|_| remove_version(&current, &installed, cvm_home));
+        let is_removed = removed.is_ok();
+        outcomes.push(outcome(&current, tags.len(), removed));
 
-        return Ok(());
+        if is_removed {
+            fallback = select_fallback(args.fallback.as_deref(), cvm_home)
+                .map(|selected| outcomes.extend(selected));
+        }
     }
 
-    println!("Checking if the version we are removing is in use...");
-    let version = current_version(cvm_home)?;
-    if *version == *tag {
-        println!(
-            "Cannot remove this version since its the current version selected.\nSwitch before removing. Or use --all to remove everything"
-        );
-        return Ok(());
+    if cli::json() && !outcomes.is_empty() {
+        report::outcomes(&outcomes);
+    }
+
+    let failed = outcomes
+        .iter()
+        .any(|outcome| matches!(outcome.status, Status::Failed));
+    match fallback {
+        Err(error) if failed => log::error(&error.to_string()),
+        fallback => fallback?,
     }
 
-    println!("Checking if the version we are trying to remove is installed...");
-    let mut found_index = None;
-    for i in 0..installed.len() {
-        if installed[i] == tag {
-            println!("Version found...");
-            found_index = Some(i);
-            break;
+    report::failures(&mut outcomes, tags.len())
+}
+
+/// The outcome of removing one of `count` versions. When several are removed
+/// failures are logged in text mode as they happen.
+fn outcome(tag: &str, count: usize, removed: Result<Outcome, Error>) -> Outcome {
+    removed.unwrap_or_else(|error| {
+        if count > 1 && !cli::json() {
+            log::error(&error.to_string());
         }
+
+        Outcome::failed(tag, error)
+    })
+}
+
+/// Removes everything in the cvm home, or with --keep-cache everything but
+/// the archive cache and the config, or with --bins-only the installed
+/// versions. Only directories with the cvm home marker are touched, a
+/// `cache_dir` outside the home is left alone.
+fn remove_all(args: &RemoveArgs, cvm_home: &Path) -> Result<(), Error> {
+    if !cvm_home.join(crate::CVM_MARKER).is_file() {
+        return Err(Error::new(
+            ErrorKind::StateCorrupt,
+            format!(
+                "'{}' does not look like a cvm home, it has no {} file. Refusing to remove it.",
+                cvm_home.display(),
+                crate::CVM_MARKER
+            ),
+        ));
     }
 
-    if found_index.is_none() {
-        println!(
-            "Selected CMake version v{} is not installed. Therefore cannot be removed",
-            tag
+    let installed = installed(cvm_home).unwrap_or_default();
+    let bins = cvm_home.join(crate::CVM_BINS);
+    let cache = cache_dir(cvm_home)?;
+    let config = cvm_home.join(crate::CVM_CONFIG);
+
+    // A cache_dir outside the cvm home may be shared, it is never removed.
+    let cache_in_home = cache.starts_with(cvm_home);
+
+    // Sizes of what goes, linked versions are not ours and not counted.
+    let bins_bytes = utils::dir_size(&bins);
+    let cache_bytes = utils::dir_size(&cache);
+    let home_cache_bytes = if cache_in_home { cache_bytes } else { 0 };
+    let config_bytes = std::fs::metadata(&config).map_or(0, |metadata| metadata.len());
+    let other_bytes = utils::dir_size(cvm_home).saturating_sub(bins_bytes + home_cache_bytes);
+
+    status!("This removes:");
+    print_row(
+        &format!("{} installed versions", installed.len()),
+        bins_bytes,
+        &bins,
+    );
+    if args.keep_cache {
+        print_row(
+            "Logs and tracking files",
+            other_bytes.saturating_sub(config_bytes),
+            cvm_home,
         );
-        return Ok(());
+    } else if !args.bins_only {
+        if cache_in_home {
+            print_row("Archive cache", cache_bytes, &cache);
+        }
+        print_row("Settings and logs", other_bytes, cvm_home);
     }
 
-    let index = found_index.unwrap();
-    let version = installed.remove(index);
+    let freed = match (args.keep_cache, args.bins_only) {
+        (_, true) => bins_bytes,
+        (true, false) => (bins_bytes + other_bytes).saturating_sub(config_bytes),
+        (false, false) => bins_bytes + home_cache_bytes + other_bytes,
+    };
+    status!("    {:<24} {:>10}", "Total", utils::format_size(freed));
 
-    println!("Removing version installation...");
-    let dir_to_rm = cvm_home
-        .join(crate::CVM_BINS)
-        .join(format!("cmake-{}", version));
+    if args.keep_cache {
+        status!(
+            "The archive cache in '{}' and the settings in '{}' are kept.",
+            cache.display(),
+            config.display()
+        );
+    } else if !args.bins_only && !cache_in_home {
+        status!(
+            "The archive cache in '{}' is outside the cvm home and is kept.",
+            cache.display()
+        );
+    }
 
-    std::fs::remove_dir_all(dir_to_rm.clone()).map_err(|error| {
-        Rc::from(format!(
-            "Failed to remove '{}'. ({})",
-            dir_to_rm.to_str().unwrap_or(""),
-            error
-        ))
-    })?;
+    if !cli::confirm("Remove them?")? {
+        return Err(Error::new(ErrorKind::UserCancelled, CANCELLED_STR));
+    }
 
-    let file_path = cvm_home.join(crate::CVM_INSTALLED);
-    let mut file = std::fs::OpenOptions::new()
-        .write(true)
-        .open(file_path)
-        .map_err(map_error!("Failed to open cvm_current file. ({})"))?;
+    // Taken after the question so other cvm commands do not wait on it.
+    let lock = lock::acquire(cvm_home)?;
+    let installed = crate::releases::installed(cvm_home).unwrap_or_default();
 
-    file.set_len(0)
-        .map_err(map_error!("Failed to clear cvm_installed file. ({})"))?;
+    if args.bins_only {
+        status!("Removing installed versions...");
+        std::fs::remove_dir_all(&bins).map_err(map_error!(Io, "Failed to remove bins."))?;
+        std::fs::create_dir(&bins).map_err(map_error!(Io, "Failed to create bins."))?;
 
-    for version in installed {
-        let version = format!("{}\n", version.as_ref());
+        for file in TRACKING_FILES {
+            let path = cvm_home.join(file);
+            if path.exists() {
+                std::fs::write(&path, "").map_err(|error| {
+                    let message = format!("Failed to clear '{}'.", path.display());
+                    Error::new(ErrorKind::Io, message).caused_by(error)
+                })?;
+            }
+        }
+    } else if args.keep_cache {
+        status!("Removing everything but the archive cache and the settings...");
+        clear_home(cvm_home, |path| {
+            cache.starts_with(path) || path == config || path.ends_with(crate::CVM_MARKER)
+        })?;
+    } else {
+        status!("Removing '{}'...", cvm_home.display());
+        clear_home(cvm_home, |_| false)?;
 
-        file.write(version.as_bytes())
-            .map_err(map_error!("Failed to write current install to file. ({})"))?;
+        // The lock file goes last, once nothing else is left to guard.
+        drop(lock);
+        std::fs::remove_dir_all(cvm_home)
+            .map_err(map_error!(Io, "Failed to remove $HOME/.cvm directory."))?;
     }
 
-    println!("Successfully removed CMake v{}.", tag);
+    let message = format!(
+        "Removed {} versions and freed {}.",
+        installed.len(),
+        utils::format_size(freed)
+    );
+
+    if cli::json() {
+        let outcomes = installed
+            .iter()
+            .map(|version| Outcome::new(version, Status::Removed, &message))
+            .collect::<Vec<Outcome>>();
+
+        report::outcomes(&outcomes);
+    } else {
+        println!("{}", message);
+    }
 
     Ok(())
 }
 
-fn get_tag(args: &Vec<Rc<str>>, installed: &Vec<Rc<str>>) -> Result<Rc<str>, Rc<str>> {
-    if args.len() == 3 {
-        return Ok(args[2].clone().into());
+fn print_row(name: &str, bytes: u64, path: &Path) {
+    status!(
+        "    {:<24} {:>10}  '{}'",
+        name,
+        utils::format_size(bytes),
+        path.display()
+    );
+}
+
+/// Removes every entry of the cvm home but the lock file and those `keep`
+/// accepts.
+fn clear_home(cvm_home: &Path, keep: impl Fn(&Path) -> bool) -> Result<(), Error> {
+    let entries = std::fs::read_dir(cvm_home)
+        .map_err(map_error!(Io, "Failed to read $HOME/.cvm directory."))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name() == lock::LOCK_FILE || keep(&path) {
+            continue;
+        }
+
+        let removed = match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => std::fs::remove_dir_all(&path),
+            _ => std::fs::remove_file(&path),
+        };
+
+        removed.map_err(|error| {
+            let message = format!("Failed to remove '{}'.", path.display());
+            Error::new(ErrorKind::Io, message).caused_by(error)
+        })?;
     }
 
-    if installed.is_empty() {
-        println!("There are no CMake version that is installed that we track.");
-        return Ok("".into());
+    Ok(())
+}
+
+fn remove_version(tag: &str, installed: &[Arc<str>], cvm_home: &Path) -> Result<Outcome, Error> {
+    status!("Checking if the version we are trying to remove is installed...");
+    if !is_installed(tag, installed) {
+        return Err(Error::new(
+            ErrorKind::NotInstalled,
+            format!(
+                "Selected CMake version v{} is not installed. Therefore cannot be removed",
+                tag
+            ),
+        ));
     }
 
-    let mut builder = List::<Rc<str>>::new("Please select a cmake verson to remove:");
+    status!("Version found...");
+
+    remove_installed(cvm_home, tag)?;
 
-    for i in 0..installed.len() {
-        builder = builder.add_item(installed[i].as_ref(), installed[i].clone());
+    let message = format!("Successfully removed CMake v{}.", tag);
+    if !cli::json() {
+        println!("{}", message);
     }
 
-    let result = builder.inquire();
+    Ok(Outcome::new(tag, Status::Removed, &message))
+}
+
+/// Clears the selection so the selected version can be removed like any
+/// other version.
+fn deselect(version: &str, cvm_home: &Path) -> Result<(), Error> {
+    status!("Deselecting v{}...", version);
+    switch::unselect(cvm_home)
+}
+
+/// Selects another version after the selected one was removed. `fallback`,
+/// or `remove_fallback` in the cvm config, is `newest` (the default), `none`
+/// or a version.
+fn select_fallback(fallback: Option<&str>, cvm_home: &Path) -> Result<Option<Outcome>, Error> {
+    let fallback = match fallback {
+        Some(fallback) => fallback.to_string(),
+        None => config::value(cvm_home, "remove_fallback")?
+            .unwrap_or_else(|| NEWEST_FALLBACK_STR.to_string()),
+    };
 
-    match result {
-        Ok(selected) => Ok(selected.into()),
-        Err(inq_msg) => match inq_msg {
-            InquiryMessage::CloseRequested => {
-                println!("\nSession was canceled. Exiting...");
-                Ok("".into())
+    let installed = installed(cvm_home)?;
+    let version = match fallback.as_str() {
+        NO_FALLBACK_STR => None,
+        // Releases are preferred over nightlies, release candidates and named
+        // versions.
+        NEWEST_FALLBACK_STR => installed
+            .iter()
+            .filter(|version| utils::is_version_number(version))
+            .max_by(|a, b| utils::compare_versions(a, b))
+            .or_else(|| {
+                installed
+                    .iter()
+                    .max_by(|a, b| utils::compare_versions(a, b))
+            })
+            .cloned(),
+        version if is_installed(version, &installed) => Some(version.into()),
+        version => {
+            log::warning(&format!("Fallback v{} is not installed.", version));
+            None
+        }
+    };
+
+    let version = match version {
+        Some(version) => version,
+        None => {
+            log::warning(NO_SELECTION_STR);
+            return Ok(None);
+        }
+    };
+
+    status!("Switching to v{}...", version);
+    switch(&version, cvm_home)?;
+
+    let message = format!("Selected CMake v{} instead.", version);
+    if !cli::json() {
+        println!("{}", message);
+    }
+
+    Ok(Some(Outcome::new(&version, Status::Switched, &message)))
+}
+
+/// Deletes an installed version that is not selected and stops tracking it.
+/// Linked versions only get unregistered.
+pub fn remove_installed(cvm_home: &Path, version: &str) -> Result<(), Error> {
+    let mut installed = installed(cvm_home)?;
+    let index = installed
+        .iter()
+        .position(|installed_version| installed_version.as_ref() == version)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotInstalled,
+                format!("CMake v{} is not installed.", version),
+            )
+        })?;
+
+    installed.remove(index);
+
+    let dir_to_rm = cvm_home
+        .join(crate::CVM_BINS)
+        .join(format!("cmake-{}", version));
+
+    if is_linked(version, &linked(cvm_home)?) {
+        // Linked versions point at someone else's install. Only drop the link.
+        status!("Unregistering linked version...");
+        utils::remove_link(&dir_to_rm).map_err(map_error!(Io, "Failed to remove link."))?;
+
+        unset_linked(cvm_home, version)?;
+    } else {
+        status!("Removing version installation...");
+        std::fs::remove_dir_all(dir_to_rm.clone()).map_err(|error| {
+            let message = format!("Failed to remove '{}'.", dir_to_rm.to_str().unwrap_or(""));
+            Error::new(ErrorKind::Io, message).caused_by(error)
+        })?;
+    }
+
+    // The installed list goes last, a failure before leaves it untouched.
+    unset_origin(cvm_home, version)?;
+    unset_last_used(cvm_home, version)?;
+    unset_protected(cvm_home, version)?;
+
+    write_installed(cvm_home, &installed)
+}
+
+/// Resolves the versions to remove. Patterns such as `3.20.*` match installed
+/// versions, `*` stands for any text and `?` for one character.
+fn get_tags(
+    versions: &[String],
+    installed: &[Arc<str>],
+    cvm_home: &Path,
+) -> Result<Vec<Arc<str>>, Error> {
+    if !versions.is_empty() {
+        let mut tags: Vec<Arc<str>> = Vec::new();
+
+        for version in versions {
+            if !version.contains(['*', '?']) {
+                tags.push(version.as_str().into());
+                continue;
+            }
+
+            let mut matched = installed
+                .iter()
+                .filter(|installed| matches_pattern(version, installed))
+                .cloned()
+                .collect::<Vec<Arc<str>>>();
+
+            if matched.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::NotInstalled,
+                    format!("No installed version matches '{}'.", version),
+                ));
+            }
+
+            matched.sort_by(|a, b| utils::compare_versions(b, a));
+            tags.extend(matched);
+        }
+
+        let mut unique = Vec::new();
+        for tag in tags {
+            if !unique.contains(&tag) {
+                unique.push(tag);
+            }
+        }
+
+        return Ok(unique);
+    }
+
+    // Removing whatever a project pins would be a surprise, so no fallback.
+    if !cli::interactive() {
+        return Err(cli::no_input_error(NO_VERSION_HINT_STR));
+    }
+
+    if installed.is_empty() {
+        println!("There are no CMake version that is installed that we track.");
+        return Ok(Vec::new());
+    }
+
+    let choices = picker::installed_choices(installed, cvm_home);
+    picker::pick("Please select cmake versions to remove:", &choices, true)
+}
+
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    match pattern.chars().next() {
+        None => text.is_empty(),
+        Some('*') => {
+            let rest = &pattern[1..];
+            (0..=text.len())
+                .filter(|index| text.is_char_boundary(*index))
+                .any(|index| matches_pattern(rest, &text[index..]))
+        }
+        Some(letter) => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(first) if letter == '?' || letter == first => {
+                    matches_pattern(&pattern[letter.len_utf8()..], chars.as_str())
+                }
+                _ => false,
             }
-            _ => Err("Inquiry failed. exiting session".into()),
-        },
+        }
     }
 }
 
-use std::io::Write;
-use std::path::Path;
-use std::rc::Rc;
+const CANCELLED_STR: &str = "Session was canceled.";
+const NO_VERSION_HINT_STR: &str = "Pass the version to remove.";
+const NEWEST_FALLBACK_STR: &str = "newest";
+const NO_FALLBACK_STR: &str = "none";
+const NO_SELECTION_STR: &str =
+    "No CMake version is selected now. Use 'cvm switch <version>' to select one.";
+
+/// Files that track installed versions, cleared by --bins-only.
+const TRACKING_FILES: [&str; 6] = [
+    crate::CVM_INSTALLED,
+    crate::CVM_CURRENT_FILE,
+    crate::CVM_LINKED,
+    crate::CVM_ORIGINS,
+    crate::CVM_USED,
+    crate::CVM_PROTECTED,
+];
 
-use term_inquiry::{InquiryMessage, List};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::macros::map_error;
+use crate::cli::{self, RemoveArgs};
+use crate::config;
+use crate::error::{Error, ErrorKind};
+use crate::install::cache_dir;
+use crate::lock;
+use crate::log;
+use crate::macros::{map_error, status};
+use crate::picker;
 use crate::releases::current_version;
-use crate::releases::installed;
+use crate::releases::{installed, is_installed};
+use crate::releases::{is_linked, linked, unset_linked, unset_origin};
+use crate::releases::{unset_last_used, unset_protected, write_installed};
+use crate::report::{self, Outcome, Status};
+use crate::switch::{self, switch};
+use crate::utils;
@@ -0,0 +1,122 @@
+/// What a command did to a version. Part of the --json schema, variants are
+/// only ever added.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Installed,
+    Switched,
+    Selected,
+    AlreadyInstalled,
+    Downloaded,
+    Cached,
+    Removed,
+    Adopted,
+    Linked,
+    Built,
+    Pruned,
+    Pinned,
+    Failed,
+}
+
+/// One entry of the `results` array in --json output.
+#[derive(Serialize)]
+pub struct Outcome {
+    pub version: Arc<str>,
+    pub status: Status,
+    pub message: Arc<str>,
+    /// Why a `Failed` outcome failed, see `failures`.
+    #[serde(skip)]
+    pub error: Option<Error>,
+}
+
+impl Outcome {
+    pub fn new(version: &str, status: Status, message: &str) -> Self {
+        Outcome {
+            version: version.into(),
+            status,
+            message: message.into(),
+            error: None,
+        }
+    }
+
+    pub fn failed(version: &str, error: Error) -> Self {
+        let message = error.to_string();
+        Outcome {
+            error: Some(error),
+            ..Outcome::new(version, Status::Failed, &message)
+        }
+    }
+}
+
+/// The error of a command that handled `total` versions, `Ok` when none
+/// failed. A single failure is returned as is and failures of one kind keep
+/// their kind, so the exit code still tells what went wrong.
+pub fn failures(outcomes: &mut [Outcome], total: usize) -> Result<(), Error> {
+    let mut errors = outcomes
+        .iter_mut()
+        .filter_map(|outcome| outcome.error.take())
+        .collect::<Vec<Error>>();
+
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        failed => {
+            let kind = errors[0].kind();
+            let kind = if errors.iter().all(|error| error.kind() == kind) {
+                kind
+            } else {
+                ErrorKind::Other
+            };
+
+            Err(Error::new(
+                kind,
+                format!("{} of {} versions failed.", failed, total),
+            ))
+        }
+    }
+}
+
+/// Reports the result of a command for a single version. People get
+/// `message`, --json prints it as the only entry of `results`.
+pub fn outcome(version: &str, status: Status, message: &str) {
+    if cli::json() {
+        outcomes(&[Outcome::new(version, status, message)]);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Prints `{"results": [...]}`. Only used with --json, commands print their
+/// own summary for people.
+pub fn outcomes(outcomes: &[Outcome]) {
+    print_json(&json!({ "results": outcomes }));
+}
+
+/// Prints an error as `{"error": {"code": ..., "kind": ..., ...}}`. The code
+/// is the exit code of the process.
+pub fn error(error: &Error) {
+    print_json(&json!({
+        "error": {
+            "code": error.kind().exit_code(),
+            "kind": error.kind(),
+            "message": error.message(),
+            "causes": error.causes(),
+        }
+    }));
+}
+
+pub fn print_json(value: &impl Serialize) {
+    match serde_json::to_string(value) {
+        Ok(text) => println!("{}", text),
+        Err(error) => log::error(&format!("Failed to write JSON output. ({})", error)),
+    }
+}
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::cli;
+use crate::error::{Error, ErrorKind};
+use crate::log;
@@ -1,81 +1,159 @@
-fn validate_caches(cvm_home: &Path) -> Result<(), Rc<str>> {
-    let file = fs::OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(cvm_home.join(crate::CVM_CACHE))
-        .map_err(map_error!("Failed to open cvm_cache file. ({})"))?;
+fn validate_caches(cvm_home: &Path) -> Result<(), Error> {
+    let file = fs::File::open(cvm_home.join(crate::CVM_CACHE))
+        .map_err(map_error!(StateCorrupt, "Failed to open cvm_cache file."))?;
 
     let mut reader = std::io::BufReader::new(file);
-    let latest_release = latest_release()?;
+    let sources = sources::configured(cvm_home)?;
+    let latest_release = sources::latest(&sources)?;
     let mut line = String::new();
 
-    reader
-        .read_line(&mut line)
-        .map_err(map_error!("Failed to read first line in cache file. ({})"))?;
+    reader.read_line(&mut line).map_err(map_error!(
+        StateCorrupt,
+        "Failed to read first line in cache file."
+    ))?;
 
-    line.pop();
+    // The first line starts with the newest release, check if cache is updated.
+    let cached_latest = line.trim_end().split('\t').next().unwrap_or("");
+    if cached_latest == latest_release.as_ref() {
+        return Ok(());
+    }
+
+    status!("New release detected updating available versions...");
+    let releases = sources::releases(&sources)?;
+    set_cached_releases(cvm_home, &releases)
+}
 
-    // check if cache is updated.
-    if line == *latest_release {
+pub fn setup_cvm(cvm_home: &Path) -> Result<(), Error> {
+    if cvm_home.exists() {
+        // Homes set up before the marker existed get one, other directories
+        // never do.
+        let is_cvm_home = cvm_home.join(crate::CVM_INSTALLED).is_file()
+            && cvm_home.join(crate::CVM_CURRENT_FILE).is_file();
+        if is_cvm_home && !cvm_home.join(crate::CVM_MARKER).exists() {
+            write_marker(cvm_home)?;
+        }
+
+        // 'remove --all --keep-cache' leaves only the marker and archives.
+        if cvm_home.join(crate::CVM_MARKER).exists() {
+            create_state(cvm_home)?;
+            migrate_current(cvm_home)?;
+        }
+
+        refresh_caches(cvm_home);
         return Ok(());
     }
 
-    println!("New release detected updating available versions...");
-    let releases = releases()?;
-    let mut file = reader.into_inner();
+    status!("'.cvm' directory is not set up. Setting up now...");
+
+    log::info("Creating cvm home directory...");
+    fs::create_dir(cvm_home).map_err(map_error!(Io, "Failed to create directory."))?;
+    write_marker(cvm_home)?;
+    create_state(cvm_home)?;
+
+    refresh_caches(cvm_home);
+    Ok(())
+}
 
-    file.set_len(0)
-        .map_err(map_error!("Failed to clear cache file. ({})"))?;
+/// Creates the directories and tracking files that are missing.
+fn create_state(cvm_home: &Path) -> Result<(), Error> {
+    if !cvm_home.join(crate::CVM_BINS).exists() {
+        log::info("Creating bins directory...");
+        fs::create_dir(cvm_home.join(crate::CVM_BINS))
+            .map_err(map_error!(Io, "Failed to create bins directory."))?;
+    }
 
-    file.seek(std::io::SeekFrom::Start(0))
-        .map_err(map_error!("Failed to seek to beginning of file. ({})"))?;
+    if !cvm_home.join(crate::CVM_CACHE).exists() {
+        log::info("Creating file to cache available versions...");
+        fs::File::create(cvm_home.join(crate::CVM_CACHE))
+            .map_err(map_error!(StateCorrupt, "Failed to create cvm_cache file."))?;
+    }
+
+    if !cvm_home.join(crate::CVM_INSTALLED).exists() {
+        log::info("Creating file to track installed versions...");
+        fs::File::create(cvm_home.join(crate::CVM_INSTALLED))
+            .map_err(map_error!(Io, "Failed to create cvm_installed file."))?;
+    }
 
-    for release in releases {
-        let release = format!("{}\n", release);
-        file.write(release.as_bytes())
-            .map_err(map_error!("Failed to write release to cache file. ({})"))?;
+    if !cvm_home.join(crate::CVM_LINKED).exists() {
+        log::info("Creating file to track linked versions...");
+        fs::File::create(cvm_home.join(crate::CVM_LINKED))
+            .map_err(map_error!(Io, "Failed to create cvm_linked file."))?;
+    }
+
+    if !cvm_home.join(crate::CVM_CURRENT_FILE).exists() {
+        log::info("Creating file to track currently installed version...");
+        fs::File::create(cvm_home.join(crate::CVM_CURRENT_FILE))
+            .map_err(map_error!(Io, "Failed to create cvm_current file."))?;
     }
 
     Ok(())
 }
 
-pub fn setup_cvm(cvm_home: &Path) -> Result<(), Rc<str>> {
-    if cvm_home.exists() {
-        validate_caches(cvm_home)?;
+/// Homes from older cvm versions keep the selected version in `bins/current`
+/// itself. Moves it back to `bins/cmake-<version>` and links it instead.
+fn migrate_current(cvm_home: &Path) -> Result<(), Error> {
+    let bins = cvm_home.join(crate::CVM_BINS);
+    let is_old_layout = |version: &str| {
+        !version.is_empty()
+            && bins.join(crate::CVM_CURRENT_DIR).symlink_metadata().is_ok()
+            && bins
+                .join(format!("cmake-{}", version))
+                .symlink_metadata()
+                .is_err()
+    };
+
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+    if !is_old_layout(&current) {
         return Ok(());
     }
 
-    println!("'.cvm' directory is not set up. Setting up now...");
-
-    log::info("Creating cvm home directory...");
-    fs::create_dir(cvm_home).map_err(map_error!("Failed to create directory. ({})"))?;
+    // Another cvm process may have moved it while this one waited.
+    let _lock = lock::acquire(cvm_home)?;
+    let current = current_version(cvm_home).unwrap_or_else(|_| "".into());
+    if !is_old_layout(&current) {
+        return Ok(());
+    }
 
-    log::info("Creating bins directory...");
-    fs::create_dir(cvm_home.join(crate::CVM_BINS))
-        .map_err(map_error!("Failed to create bins directory. ({})"))?;
+    log::info("Moving the selected version out of the current directory...");
+    fs::rename(
+        bins.join(crate::CVM_CURRENT_DIR),
+        bins.join(format!("cmake-{}", current)),
+    )
+    .map_err(map_error!(Io, "Failed to rename directory."))?;
 
-    log::info("Creating file to cache available versions...");
-    fs::File::create(cvm_home.join(crate::CVM_CACHE))
-        .map_err(map_error!("Failed to create cvm_cache file. ({})"))?;
+    switch::switch(&current, cvm_home)
+}
 
-    log::info("Creating file to track installed versions...");
-    fs::File::create(cvm_home.join(crate::CVM_INSTALLED))
-        .map_err(map_error!("Failed to create cvm_installed file. ({})"))?;
+/// Marks the directory as a cvm home. 'remove --all' only deletes marked
+/// directories.
+fn write_marker(cvm_home: &Path) -> Result<(), Error> {
+    fs::write(cvm_home.join(crate::CVM_MARKER), MARKER_STR)
+        .map_err(map_error!(Io, "Failed to create cvm home marker."))
+}
 
-    log::info("Creating file to track currently installed version...");
-    fs::File::create(cvm_home.join(crate::CVM_CURRENT_FILE))
-        .map_err(map_error!("Failed to create cvm_current file. ({})"))?;
+/// Failing to reach github should not stop commands that work without it,
+/// such as installing from a local archive.
+fn refresh_caches(cvm_home: &Path) {
+    if cli::offline() {
+        return;
+    }
 
-    validate_caches(cvm_home)?;
-    Ok(())
+    if let Err(error) = validate_caches(cvm_home) {
+        log::warning(&format!("Could not refresh available versions. {}", error));
+    }
 }
 
+const MARKER_STR: &str = "This directory is managed by cvm. 'cvm remove --all' deletes it.\n";
+
 use std::fs;
-use std::io::Seek;
-use std::io::{BufRead, Write};
+use std::io::BufRead;
 use std::path::Path;
-use std::rc::Rc;
 
+use crate::cli;
+use crate::error::Error;
+use crate::lock;
 use crate::log;
-use crate::macros::map_error;
-use crate::releases::{latest_release, releases};
+use crate::macros::{map_error, status};
+use crate::releases::{current_version, set_cached_releases};
+use crate::sources;
+use crate::switch;
@@ -0,0 +1,585 @@
+/// A place CMake releases can be listed and downloaded from.
+#[derive(Clone)]
+pub enum Source {
+    /// The github releases of Kitware/CMake.
+    GitHub,
+    /// A web server with the cmake.org/files directory layout. This is used
+    /// for cmake.org itself as well as internal mirrors.
+    Index(Arc<str>),
+    /// A local directory holding release archives, either flat or in
+    /// `v<major>.<minor>` sub directories.
+    Local(PathBuf),
+}
+
+/// Where a release archive can be fetched from.
+pub enum Location {
+    Url(String),
+    File(PathBuf),
+}
+
+impl Source {
+    /// Parses `github`, `cmake.org`, `mirror:<url>` or `local:<path>`.
+    pub fn parse(spec: &str) -> Result<Self, Error> {
+        let spec = spec.trim();
+        match spec.split_once(':') {
+            _ if spec == "github" => Ok(Source::GitHub),
+            _ if spec == "cmake.org" => Ok(Source::Index(CMAKE_ORG_FILES.into())),
+            Some(("mirror", url)) if !url.is_empty() => {
+                Ok(Source::Index(url.trim_end_matches('/').into()))
+            }
+            Some(("local", path)) if !path.is_empty() => Ok(Source::Local(PathBuf::from(path))),
+            _ => Err(Error::new(
+                ErrorKind::Usage,
+                format!("Unknown release source '{}'. {}", spec, SOURCE_HINT_STR),
+            )),
+        }
+    }
+
+    /// Lists the versions available from this source, newest first.
+    pub fn releases(&self) -> Result<Vec<ReleaseInfo>, Error> {
+        let versions = match self {
+            Source::GitHub => return releases::releases(),
+            Source::Index(url) => index_releases(url)?,
+            Source::Local(path) => local_releases(path)?,
+        };
+
+        Ok(versions.into_iter().map(ReleaseInfo::new).collect())
+    }
+
+    /// Lists the nightly builds available from this source, newest first.
+    pub fn nightlies(&self) -> Result<Vec<Arc<str>>, Error> {
+        let mut nightlies = match self {
+            Source::GitHub => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    "github does not publish nightly builds.",
+                ))
+            }
+            Source::Index(url) => {
+                let listing = get_text(&format!("{}/{}/", url, NIGHTLY_DIR))?;
+                listing_versions(&links(&listing))
+            }
+            Source::Local(path) => local_versions(path)?,
+        };
+
+        nightlies.retain(|version| utils::is_nightly(version));
+        sort_newest_first(&mut nightlies);
+        Ok(nightlies)
+    }
+
+    pub fn latest(&self) -> Result<Arc<str>, Error> {
+        match self {
+            Source::GitHub => releases::latest_release(),
+            Source::Index(url) => index_latest(url),
+            Source::Local(path) => local_releases(path)?.into_iter().next().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("No releases found in {}.", self),
+                )
+            }),
+        }
+    }
+
+    /// Returns where the archive `file_name` of `version` can be found. The
+    /// github source uses the url the release was resolved with. Nightlies
+    /// are kept in the `dev` directory.
+    pub fn locate(
+        &self,
+        file_name: &str,
+        default_url: &str,
+        version: &utils::Version,
+    ) -> Result<Location, Error> {
+        match self {
+            Source::GitHub => Ok(Location::Url(default_url.to_string())),
+            Source::Index(url) => Ok(Location::Url(format!(
+                "{}/{}/{}",
+                url,
+                series_dir(version),
+                file_name
+            ))),
+            Source::Local(path) => {
+                let series = path.join(series_dir(version));
+                [path.join(file_name), series.join(file_name)]
+                    .iter()
+                    .find(|candidate| candidate.is_file())
+                    .map(|found| Location::File(found.clone()))
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::NotFound,
+                            format!("'{}' was not found in {}.", file_name, self),
+                        )
+                    })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::GitHub => write!(f, "github"),
+            Source::Index(url) if url.as_ref() == CMAKE_ORG_FILES => write!(f, "cmake.org"),
+            Source::Index(url) => write!(f, "mirror:{}", url),
+            Source::Local(path) => write!(f, "local:{}", path.to_str().unwrap_or("")),
+        }
+    }
+}
+
+/// Parses a comma separated list of sources, tried in the given order.
+pub fn parse_list(specs: &str) -> Result<Vec<Source>, Error> {
+    let sources = specs
+        .split(',')
+        .filter(|spec| !spec.trim().is_empty())
+        .map(Source::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if sources.is_empty() {
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("No release sources given. {}", SOURCE_HINT_STR),
+        ));
+    }
+
+    Ok(sources)
+}
+
+/// The sources set with `sources = ...` in the cvm config file. Defaults to
+/// github with cmake.org as fallback.
+pub fn configured(cvm_home: &Path) -> Result<Vec<Source>, Error> {
+    match config::value(cvm_home, "sources")? {
+        Some(specs) => parse_list(&specs),
+        None => Ok(vec![Source::GitHub, Source::Index(CMAKE_ORG_FILES.into())]),
+    }
+}
+
+/// Returns the nightlies of the first source that publishes them.
+pub fn nightlies(sources: &[Source]) -> Result<Vec<Arc<str>>, Error> {
+    first_success(sources, |source| source.nightlies())
+}
+
+/// Returns the releases of the first source that can be reached.
+pub fn releases(sources: &[Source]) -> Result<Vec<ReleaseInfo>, Error> {
+    first_success(sources, |source| source.releases())
+}
+
+/// Returns the newest release of the first source that can be reached.
+pub fn latest(sources: &[Source]) -> Result<Arc<str>, Error> {
+    first_success(sources, |source| source.latest())
+}
+
+fn first_success<T>(
+    sources: &[Source],
+    action: impl Fn(&Source) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut errors = Vec::new();
+    let mut kind = ErrorKind::NotFound;
+
+    for source in sources {
+        match action(source) {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                // Report the kind of the first source, it is the preferred one.
+                if errors.is_empty() {
+                    kind = error.kind();
+                }
+
+                errors.push(format!("{}: {}", source, error));
+            }
+        }
+    }
+
+    Err(Error::new(
+        kind,
+        format!("No release source could be used.\n{}", errors.join("\n")),
+    ))
+}
+
+fn index_releases(url: &str) -> Result<Vec<Arc<str>>, Error> {
+    let mut releases = Vec::new();
+
+    for series in index_series(url)? {
+        let listing = get_text(&format!("{}/{}/", url, series))?;
+        releases.extend(listing_versions(&links(&listing)));
+    }
+
+    sort_newest_first(&mut releases);
+    Ok(releases)
+}
+
+fn index_latest(url: &str) -> Result<Arc<str>, Error> {
+    let series = index_series(url)?;
+    let newest = series.first().ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("No release directories found at '{}'.", url),
+        )
+    })?;
+
+    let listing = get_text(&format!("{}/{}/", url, newest))?;
+    let mut releases = listing_versions(&links(&listing));
+    sort_newest_first(&mut releases);
+
+    releases.into_iter().next().ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("No releases found at '{}/{}'.", url, newest),
+        )
+    })
+}
+
+/// Returns the `v<major>.<minor>` directories of an index, newest first. Only
+/// the 3.x series and later are listed.
+fn index_series(url: &str) -> Result<Vec<String>, Error> {
+    let listing = get_text(&format!("{}/", url))?;
+
+    let mut series = links(&listing)
+        .into_iter()
+        .filter_map(|link| {
+            let name = link.trim_end_matches('/');
+            let (major, minor) = name.strip_prefix('v')?.split_once('.')?;
+            let major = major.parse::<i32>().ok()?;
+            let minor = minor.parse::<i32>().ok()?;
+            Some((major, minor, name.to_string()))
+        })
+        .filter(|(major, _, _)| *major >= 3)
+        .collect::<Vec<_>>();
+
+    series.sort();
+    series.dedup();
+
+    Ok(series.into_iter().rev().map(|(_, _, name)| name).collect())
+}
+
+fn local_releases(path: &Path) -> Result<Vec<Arc<str>>, Error> {
+    let mut releases = local_versions(path)?;
+    releases.retain(|version| !utils::is_nightly(version));
+    sort_newest_first(&mut releases);
+    Ok(releases)
+}
+
+fn local_versions(path: &Path) -> Result<Vec<Arc<str>>, Error> {
+    let mut names = Vec::new();
+    let mut directories = vec![path.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let entries = std::fs::read_dir(&directory).map_err(|error| {
+            let message = format!("Failed to read '{}'.", directory.to_str().unwrap_or(""));
+            Error::new(ErrorKind::Io, message).caused_by(error)
+        })?;
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let series = name.starts_with('v') || name == NIGHTLY_DIR;
+            if entry.path().is_dir() && directory == path && series {
+                directories.push(entry.path());
+            } else {
+                names.push(name);
+            }
+        }
+    }
+
+    Ok(listing_versions(&names))
+}
+
+/// Collects the distinct versions from archive names such as
+/// `cmake-3.27.9-linux-x86_64.tar.gz`, `cmake-3.28.0-rc1.zip` or
+/// `cmake-3.28.20231001-g1a2b3c4-linux-x86_64.tar.gz`.
+fn listing_versions(names: &[String]) -> Vec<Arc<str>> {
+    let mut versions = names
+        .iter()
+        .filter_map(|name| version_from_file_name(name))
+        .collect::<Vec<_>>();
+
+    versions.sort();
+    versions.dedup();
+    versions.into_iter().map(Arc::from).collect()
+}
+
+fn version_from_file_name(name: &str) -> Option<String> {
+    let rest = name.strip_prefix("cmake-")?;
+    let rest = ARCHIVE_EXTENSIONS
+        .iter()
+        .find_map(|extension| rest.strip_suffix(extension))?;
+
+    let mut parts = rest.split('-');
+    let number = parts.next()?;
+    if !utils::is_version_number(number) {
+        return None;
+    }
+
+    match parts.next() {
+        Some(candidate) if candidate.starts_with("rc") => Some(format!("{}-{}", number, candidate)),
+        Some(commit) if utils::is_nightly(&format!("{}-{}", number, commit)) => {
+            Some(format!("{}-{}", number, commit))
+        }
+        _ => Some(number.to_string()),
+    }
+}
+
+fn series_dir(version: &utils::Version) -> String {
+    match version.date {
+        Some(_) => NIGHTLY_DIR.to_string(),
+        None => format!("v{}.{}", version.major, version.minor),
+    }
+}
+
+fn sort_newest_first(releases: &mut [Arc<str>]) {
+    releases.sort_by(|a, b| utils::compare_versions(b, a));
+}
+
+/// Pulls the href targets out of an html directory listing.
+fn links(listing: &str) -> Vec<String> {
+    listing
+        .split("href=\"")
+        .skip(1)
+        .filter_map(|part| part.split('"').next())
+        .map(|link| {
+            link.rsplit('/')
+                .find(|segment| !segment.is_empty())
+                .unwrap_or(link)
+        })
+        .map(|link| link.to_string())
+        .collect()
+}
+
+pub fn get_text(url: &str) -> Result<String, Error> {
+    cli::ensure_online(url)?;
+
+    let response = blocking::Client::new()
+        .get(url)
+        .header(
+            header::USER_AGENT,
+            format!("cvm {} request", env!("CARGO_PKG_VERSION")),
+        )
+        .send()
+        .map_err(|error| {
+            Error::new(ErrorKind::Network, format!("Failed to request '{}'.", url)).caused_by(error)
+        })?;
+
+    if !response.status().is_success() {
+        return Err(Error::new(
+            ErrorKind::Network,
+            format!("Request to '{}' failed. ({})", url, response.status()),
+        ));
+    }
+
+    response
+        .text()
+        .map_err(map_error!(Network, "Failed to read response."))
+}
+
+const CMAKE_ORG_FILES: &str = "https://cmake.org/files";
+const NIGHTLY_DIR: &str = "dev";
+const ARCHIVE_EXTENSIONS: [&str; 5] = [".tar.gz", ".tar.Z", ".zip", ".sh", ".tgz"];
+const SOURCE_HINT_STR: &str =
+    "Expected github, cmake.org, mirror:<url> or local:<path> separated by commas.";
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use reqwest::{blocking, header};
+
+use crate::cli;
+use crate::config;
+use crate::error::{Error, ErrorKind};
+use crate::macros::map_error;
+use crate::releases::{self, ReleaseInfo};
+use crate::utils;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn names(sources: &[Source]) -> Vec<String> {
+        sources.iter().map(|source| source.to_string()).collect()
+    }
+
+    /// An empty directory under the system temp directory, removed when
+    /// dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("cvm-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn touch(&self, file: &str) {
+            let path = self.0.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn parses_sources() {
+        let cases = [
+            ("github", "github"),
+            (" cmake.org ", "cmake.org"),
+            (
+                "mirror:https://mirror.example.com/cmake/",
+                "mirror:https://mirror.example.com/cmake",
+            ),
+            ("local:/srv/cmake", "local:/srv/cmake"),
+        ];
+
+        for (spec, expected) in cases {
+            assert_eq!(Source::parse(spec).unwrap().to_string(), expected);
+        }
+
+        for spec in ["", "GitHub", "ftp:host", "mirror:", "local:", "cmake.org:x"] {
+            let error = Source::parse(spec).err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::Usage, "parsing '{}'", spec);
+        }
+    }
+
+    #[test]
+    fn parses_source_lists_in_order() {
+        let sources = parse_list("local:/srv/cmake, ,mirror:http://m,github,").unwrap();
+        assert_eq!(
+            names(&sources),
+            ["local:/srv/cmake", "mirror:http://m", "github"]
+        );
+
+        for specs in ["", " , ", "github,bogus"] {
+            let error = parse_list(specs).err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::Usage, "parsing '{}'", specs);
+        }
+    }
+
+    #[test]
+    fn reads_versions_from_file_names() {
+        let cases = [
+            ("cmake-3.27.9-linux-x86_64.tar.gz", Some("3.27.9")),
+            ("cmake-3.27.9-windows-x86_64.zip", Some("3.27.9")),
+            ("cmake-3.5.2-Linux-i386.sh", Some("3.5.2")),
+            ("cmake-2.8.12-Darwin64-universal.tar.Z", Some("2.8.12")),
+            ("cmake-3.28.0-rc1.zip", Some("3.28.0-rc1")),
+            ("cmake-3.28.0-rc1-linux-x86_64.tar.gz", Some("3.28.0-rc1")),
+            (
+                "cmake-3.28.20231001-g1a2b3c4-linux-x86_64.tar.gz",
+                Some("3.28.20231001-g1a2b3c4"),
+            ),
+            ("cmake-3.27.9-SHA-256.txt", None),
+            ("cmake-3.27.9.tar.gz.asc", None),
+            ("cmake-latest.tar.gz", None),
+            ("ninja-1.11.1-linux.zip", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(
+                version_from_file_name(name).as_deref(),
+                expected,
+                "reading '{}'",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn lists_flat_local_directories() {
+        let dir = TempDir::new("flat");
+        dir.touch("cmake-3.27.9-linux-x86_64.tar.gz");
+        dir.touch("cmake-3.27.9-windows-x86_64.zip");
+        dir.touch("cmake-3.28.1-linux-x86_64.tar.gz");
+        dir.touch("cmake-3.29.20240101-gabc1234-linux-x86_64.tar.gz");
+        dir.touch("README.txt");
+
+        let source = Source::Local(dir.0.clone());
+        let releases = source.releases().unwrap();
+        let releases = releases
+            .iter()
+            .map(|release| release.version.as_ref())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(releases, ["3.28.1", "3.27.9"]);
+        assert_eq!(source.latest().unwrap().as_ref(), "3.28.1");
+        assert_eq!(
+            source.nightlies().unwrap(),
+            [Arc::from("3.29.20240101-gabc1234")]
+        );
+    }
+
+    #[test]
+    fn lists_series_local_directories() {
+        let dir = TempDir::new("series");
+        dir.touch("v3.27/cmake-3.27.9-linux-x86_64.tar.gz");
+        dir.touch("v3.28/cmake-3.28.0-rc1-linux-x86_64.tar.gz");
+        dir.touch("v3.28/cmake-3.28.1-linux-x86_64.tar.gz");
+        dir.touch("dev/cmake-3.29.20240101-gabc1234-linux-x86_64.tar.gz");
+        // Only the series directories are searched.
+        dir.touch("v3.28/old/cmake-3.10.0-Linux-x86_64.tar.gz");
+        dir.touch("other/cmake-3.20.0-linux-x86_64.tar.gz");
+
+        let versions = local_versions(&dir.0).unwrap();
+        let mut versions = versions
+            .iter()
+            .map(|version| version.as_ref())
+            .collect::<Vec<&str>>();
+        versions.sort();
+
+        assert_eq!(
+            versions,
+            ["3.27.9", "3.28.0-rc1", "3.28.1", "3.29.20240101-gabc1234"]
+        );
+
+        let version = utils::parse_version("3.28.1").unwrap();
+        let source = Source::Local(dir.0.clone());
+        match source.locate("cmake-3.28.1-linux-x86_64.tar.gz", "", &version) {
+            Ok(Location::File(path)) => {
+                assert!(path.ends_with("v3.28/cmake-3.28.1-linux-x86_64.tar.gz"))
+            }
+            _ => panic!("cmake-3.28.1 was not located"),
+        }
+    }
+
+    #[test]
+    fn first_success_tries_sources_in_order() {
+        let sources = parse_list("mirror:http://a,mirror:http://b,github").unwrap();
+        let tried = RefCell::new(Vec::new());
+
+        let found = first_success(&sources, |source| {
+            tried.borrow_mut().push(source.to_string());
+            match source {
+                Source::Index(url) if url.as_ref() == "http://a" => {
+                    Err(Error::new(ErrorKind::Network, "unreachable"))
+                }
+                _ => Ok(source.to_string()),
+            }
+        });
+
+        assert_eq!(found.unwrap(), "mirror:http://b");
+        assert_eq!(*tried.borrow(), ["mirror:http://a", "mirror:http://b"]);
+    }
+
+    #[test]
+    fn first_success_reports_every_failure() {
+        let sources = parse_list("mirror:http://a,github").unwrap();
+
+        let error = first_success(&sources, |source| -> Result<(), Error> {
+            match source {
+                Source::GitHub => Err(Error::new(ErrorKind::NotFound, "missing")),
+                _ => Err(Error::new(ErrorKind::Network, "unreachable")),
+            }
+        })
+        .err()
+        .unwrap();
+
+        // The kind of the first, preferred, source is reported.
+        assert_eq!(error.kind(), ErrorKind::Network);
+        let message = error.to_string();
+        let first = message.find("mirror:http://a: unreachable").unwrap();
+        let second = message.find("github: missing").unwrap();
+        assert!(first < second);
+    }
+}
@@ -1,48 +1,59 @@
-pub fn switch_version(args: &Vec<Rc<str>>, cvm_home: &Path) -> Result<(), Rc<str>> {
+pub fn switch_version(version: Option<&str>, cvm_home: &Path) -> Result<(), Error> {
     let installed = installed(cvm_home)?;
 
-    let tag = get_tag(args, &installed)?;
+    let tag = get_tag(version, &installed, cvm_home)?;
 
     if tag.is_empty() {
         return Ok(());
     }
 
+    let _lock = lock::acquire(cvm_home)?;
+
     // We don't mind if no versions are currently installed.
     let current = current_version(cvm_home).unwrap_or("".into());
 
     if *tag == *current {
-        println!("CMake version {} is already selected.", tag);
-        return Ok(());
-    }
-
-    println!("Checking to see if version is already installed...");
-    if !is_installed(&tag, &installed) {
-        println!(
-            "CMake version {0} is not installed. Please run 'cvm install {0}'",
-            tag
+        report::outcome(
+            &tag,
+            Status::Selected,
+            &format!("CMake version {} is already selected.", tag),
         );
         return Ok(());
     }
 
-    if !current.is_empty() {
-        let from = cvm_home.join(crate::CVM_BINS).join(crate::CVM_CURRENT_DIR);
-        let to = cvm_home
-            .join(crate::CVM_BINS)
-            .join(format!("cmake-{}", current));
-
-        std::fs::rename(from, to).map_err(map_error!("Failed to rename directory. ({})"))?;
+    status!("Checking to see if version is already installed...");
+    if !is_installed(&tag, &installed) {
+        return Err(Error::new(
+            ErrorKind::NotInstalled,
+            format!(
+                "CMake version {0} is not installed. Please run 'cvm install {0}'",
+                tag
+            ),
+        ));
     }
 
-    println!("Switching...");
+    status!("Switching...");
     switch(&tag, cvm_home)?;
 
-    println!("Successfully switch to CMake v{}", tag);
+    report::outcome(
+        &tag,
+        Status::Switched,
+        &format!("Successfully switch to CMake v{}", tag),
+    );
     Ok(())
 }
 
-fn get_tag(args: &Vec<Rc<str>>, installed: &Vec<Rc<str>>) -> Result<Rc<str>, Rc<str>> {
-    if args.len() == 3 {
-        return Ok(args[2].clone());
+fn get_tag(
+    version: Option<&str>,
+    installed: &[Arc<str>],
+    cvm_home: &Path,
+) -> Result<Arc<str>, Error> {
+    if let Some(version) = version {
+        return Ok(version.into());
+    }
+
+    if !cli::interactive() {
+        return pin::required();
     }
 
     if installed.is_empty() {
@@ -50,41 +61,61 @@ fn get_tag(args: &Vec<Rc<str>>, installed: &Vec<Rc<str>>) -> Result<Rc<str>, Rc<
         return Ok("".into());
     }
 
-    let mut builder = List::<Rc<str>>::new("Please select a cmake verson to switch to:");
-
-    for i in 0..installed.len() {
-        builder = builder.add_item(installed[i].as_ref(), installed[i].clone());
-    }
+    let choices = picker::installed_choices(installed, cvm_home);
+    let picked = picker::pick(
+        "Please select a cmake verson to switch to:",
+        &choices,
+        false,
+    )?;
 
-    let result = builder.inquire();
-
-    match result {
-        Ok(selected) => Ok(selected.into()),
-        Err(inq_msg) => match inq_msg {
-            InquiryMessage::CloseRequested => {
-                println!("\nSession was canceled. Exiting...");
-                Ok("".into())
-            }
-            _ => Err("Inquiry failed. exiting session".into()),
-        },
-    }
+    Ok(picked[0].clone())
 }
 
-pub fn switch(version: &str, cvm_home: &Path) -> Result<(), Rc<str>> {
-    let from = cvm_home
-        .join(crate::CVM_BINS)
-        .join(format!("cmake-{}", version));
-    let to = cvm_home.join(crate::CVM_BINS).join(crate::CVM_CURRENT_DIR);
+/// Selects the installed `version` by pointing `bins/current` at its
+/// directory. Every version stays in `bins/cmake-<version>`.
+pub fn switch(version: &str, cvm_home: &Path) -> Result<(), Error> {
+    let bins = cvm_home.join(crate::CVM_BINS);
+    let target = format!("cmake-{}", version);
+
+    if bins.join(&target).symlink_metadata().is_err() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("Failed to find '{}'.", bins.join(&target).display()),
+        ));
+    }
 
-    std::fs::rename(from, to).map_err(map_error!("Failed to rename directory. ({})"))?;
+    remove_current(cvm_home)?;
+    // The link is relative so the cvm home can be moved.
+    utils::symlink_dir(Path::new(&target), &bins.join(crate::CVM_CURRENT_DIR))?;
 
     set_current_install(cvm_home, version)
 }
 
-use std::path::Path;
-use std::rc::Rc;
+/// Clears the selection. The selected version stays where it is.
+pub fn unselect(cvm_home: &Path) -> Result<(), Error> {
+    remove_current(cvm_home)?;
+    set_current_install(cvm_home, "")
+}
 
-use term_inquiry::{InquiryMessage, List};
+/// Removes the `bins/current` link if there is one.
+fn remove_current(cvm_home: &Path) -> Result<(), Error> {
+    let link = cvm_home.join(crate::CVM_BINS).join(crate::CVM_CURRENT_DIR);
+    if link.symlink_metadata().is_err() {
+        return Ok(());
+    }
 
-use crate::macros::map_error;
+    utils::remove_link(&link).map_err(map_error!(Io, "Failed to remove the current link."))
+}
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::cli;
+use crate::error::{Error, ErrorKind};
+use crate::lock;
+use crate::macros::{map_error, status};
+use crate::picker;
+use crate::pin;
 use crate::releases::{current_version, installed, is_installed, set_current_install};
+use crate::report::{self, Status};
+use crate::utils;
@@ -33,43 +33,300 @@ pub fn is_version_number(version: &str) -> bool {
     true
 }
 
-#[allow(dead_code)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Version {
     pub major: i32,
     pub minor: i32,
     pub patch: i32,
+    /// Build date of a nightly as YYYYMMDD. `None` for releases.
+    pub date: Option<u32>,
+    /// Abbreviated commit a nightly was built from. `None` for releases.
+    pub commit: Option<Arc<str>>,
 }
 
-pub fn parse_version(version: &str) -> Result<Version, Rc<str>> {
+pub fn parse_version(version: &str) -> Result<Version, Error> {
     let version_split = version.split('.').collect::<Vec<&str>>();
 
     let major = version_split
-        .get(0)
-        .ok_or(Rc::from("Failed to get major version."))?
+        .first()
+        .ok_or_else(|| Error::new(ErrorKind::Usage, "Failed to get major version."))?
         .parse::<i32>()
-        .map_err(map_error!("Major value could not be parsed as an int.({})"))?;
+        .map_err(map_error!(
+            Usage,
+            "Major value could not be parsed as an int."
+        ))?;
 
     let minor = version_split
         .get(1)
-        .ok_or(Rc::from("Failed to get minor version."))?
+        .ok_or_else(|| Error::new(ErrorKind::Usage, "Failed to get minor version."))?
         .parse::<i32>()
-        .map_err(map_error!("Minor value could not be parsed as an int.({})"))?;
+        .map_err(map_error!(
+            Usage,
+            "Minor value could not be parsed as an int."
+        ))?;
 
     let patch = version_split
         .get(2)
         .unwrap_or(&"0")
         .parse::<i32>()
         .map_err(map_error!(
-            "Patch value could not be parsed as an int. ({})"
+            Usage,
+            "Patch value could not be parsed as an int."
         ))?;
 
     Ok(Version {
         major,
         minor,
         patch,
+        date: None,
+        commit: None,
     })
 }
 
-use std::rc::Rc;
+/// Parses nightly versions as published in cmake.org/files/dev, example:
+/// `3.28.20231001-g1a2b3c4`. The date takes the place of the patch number.
+pub fn parse_nightly(version: &str) -> Option<Version> {
+    let (number, commit) = version.split_once("-g")?;
+    if !is_version_number(number) || commit.is_empty() {
+        return None;
+    }
+
+    if !commit.chars().all(|letter| letter.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut parts = number.split('.');
+    let major = parts.next()?.parse::<i32>().ok()?;
+    let minor = parts.next()?.parse::<i32>().ok()?;
+    let date = parts.next().filter(|date| date.len() == 8)?;
+
+    Some(Version {
+        major,
+        minor,
+        patch: 0,
+        date: Some(date.parse::<u32>().ok()?),
+        commit: Some(commit.into()),
+    })
+}
+
+pub fn is_nightly(version: &str) -> bool {
+    parse_nightly(version).is_some()
+}
+
+/// Release candidates and nightlies are pre-releases.
+pub fn is_prerelease(version: &str) -> bool {
+    is_nightly(version) || version.contains("-rc")
+}
+
+/// Returns the major and minor version of a release or nightly.
+pub fn series(version: &str) -> Option<(i32, i32)> {
+    let version = match parse_nightly(version) {
+        Some(nightly) => nightly,
+        None => parse_version(version.split('-').next()?).ok()?,
+    };
+
+    Some((version.major, version.minor))
+}
+
+/// Orders release versions such as `3.27.9` and `3.28.0-rc1`. Release
+/// candidates come before the release they lead up to. Names that are not
+/// versions sort before all versions.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    version_key(a).cmp(&version_key(b))
+}
+
+fn version_key(version: &str) -> Option<(Version, bool, u32)> {
+    if let Some(nightly) = parse_nightly(version) {
+        return Some((nightly, true, 0));
+    }
+
+    let (number, suffix) = match version.split_once('-') {
+        Some((number, suffix)) => (number, Some(suffix)),
+        None => (version, None),
+    };
+
+    if !is_version_number(number) {
+        return None;
+    }
+
+    let parsed = parse_version(number).ok()?;
+    match suffix {
+        None => Some((parsed, true, 0)),
+        Some(suffix) => {
+            let candidate = suffix.strip_prefix("rc")?.parse::<u32>().ok()?;
+            Some((parsed, false, candidate))
+        }
+    }
+}
+
+/// Picks the versions closest in spelling to `version`, newest first. Used to
+/// suggest what was meant when a version does not exist.
+pub fn suggest(version: &str, candidates: &[Arc<str>]) -> Vec<Arc<str>> {
+    let mut scored = candidates
+        .iter()
+        .map(|candidate| (edit_distance(version, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|(a, a_version), (b, b_version)| {
+        a.cmp(b)
+            .then_with(|| compare_versions(b_version, a_version))
+    });
+
+    scored
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            row.push(substitution.min(previous[j + 1] + 1).min(row[j] + 1));
+        }
+
+        previous = row;
+    }
+
+    previous[b.len()]
+}
+
+/// Checks that a user chosen name can be used for a directory in bins.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains(['/', '\\'])
+        && name != "."
+        && name != ".."
+        && name != crate::CVM_CURRENT_DIR
+}
+
+/// Returns the directory holding the executables of an installation prefix.
+/// Handles the `CMake.app/Contents/bin` layout on macOS and `bin` everywhere
+/// else.
+pub fn bin_dir(prefix: &Path) -> PathBuf {
+    let app_bin = prefix.join("CMake.app").join("Contents").join("bin");
+    if app_bin.is_dir() {
+        return app_bin;
+    }
+
+    prefix.join("bin")
+}
+
+/// Finds the cmake executable of an installation prefix.
+pub fn cmake_binary(prefix: &Path) -> Option<PathBuf> {
+    let name = if cfg!(windows) { "cmake.exe" } else { "cmake" };
+    let path = bin_dir(prefix).join(name);
+
+    if path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Returns the bytes used by the files below `path`. Symbolic links are not
+/// followed, unreadable entries count as empty.
+pub fn dir_size(path: &Path) -> u64 {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| dir_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Creates a symbolic link to a directory.
+#[cfg(unix)]
+pub fn symlink_dir(original: &Path, link: &Path) -> Result<(), Error> {
+    std::os::unix::fs::symlink(original, link).map_err(map_error!(Io, "Failed to create symlink."))
+}
+
+/// Creates a symbolic link to a directory.
+#[cfg(windows)]
+pub fn symlink_dir(original: &Path, link: &Path) -> Result<(), Error> {
+    std::os::windows::fs::symlink_dir(original, link)
+        .map_err(map_error!(Io, "Failed to create symlink."))
+}
+
+/// Removes a symbolic link without touching what it points to. Directory
+/// links on Windows need `remove_dir`.
+pub fn remove_link(link: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(link).or_else(|_| std::fs::remove_dir(link))
+}
+
+/// Formats seconds since the unix epoch like github dates, example:
+/// `2023-12-21T14:29:47Z`.
+pub fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+
+    // Converts days since 1970-01-01 to a civil date in 400 year eras.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Formats a byte count for people, example: `1.4 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::error::{Error, ErrorKind};
 use crate::macros::map_error;
@@ -0,0 +1,64 @@
+/// Prints the absolute path of a tool such as cmake or ctest of the selected
+/// version, or of the version given with --version.
+pub fn which(args: &WhichArgs, cvm_home: &Path) -> Result<(), Error> {
+    let version = version_or_current(args.version.as_deref(), cvm_home)?;
+    let prefix = install_prefix(cvm_home, &version)?;
+
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    let path = utils::bin_dir(&prefix).join(format!("{}{}", args.tool, suffix));
+
+    if !path.is_file() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("CMake v{} has no '{}'.", version, args.tool),
+        ));
+    }
+
+    if cli::json() {
+        report::print_json(&json!({
+            "tool": args.tool,
+            "version": version.as_ref(),
+            "path": path,
+        }));
+    } else {
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Prints the install prefix of a version, the selected one by default.
+pub fn path(args: &PathArgs, cvm_home: &Path) -> Result<(), Error> {
+    let version = version_or_current(args.version.as_deref(), cvm_home)?;
+    let prefix = install_prefix(cvm_home, &version)?;
+
+    if cli::json() {
+        report::print_json(&json!({
+            "version": version.as_ref(),
+            "path": prefix,
+            "bin": utils::bin_dir(&prefix),
+        }));
+    } else {
+        println!("{}", prefix.display());
+    }
+
+    Ok(())
+}
+
+fn version_or_current(version: Option<&str>, cvm_home: &Path) -> Result<Arc<str>, Error> {
+    match version {
+        Some(version) => Ok(version.into()),
+        None => current_version(cvm_home),
+    }
+}
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::cli::{self, PathArgs, WhichArgs};
+use crate::error::{Error, ErrorKind};
+use crate::releases::{current_version, install_prefix};
+use crate::report;
+use crate::utils;